@@ -0,0 +1,61 @@
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
+
+use simple_option::msg::{
+    AdminLogResponse, ConfigResponse, ContractMetaResponse, CounterOfferHistoryResponse,
+    CounterpartiesResponse, CreationRecordResponse, DenomsResponse, DigestResponse, ExecuteMsg,
+    InstantiateMsg, IntrinsicValueResponse, MinExpiryDistanceResponse, OwnershipStatsResponse,
+    PaymentAssessmentResponse, PreviewExerciseMessagesResponse, PrivateMemoResponse, QueryMsg,
+    ReconcileResponse, ReminderExecuteMsg, RouterExecuteMsg, SettlementData,
+    SignedMetadataResponse, StatusCodeResponse, StorageLayoutResponse,
+};
+use simple_option::state::State;
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(State), &out_dir);
+    export_schema(&schema_for!(RouterExecuteMsg), &out_dir);
+    export_schema(&schema_for!(ReminderExecuteMsg), &out_dir);
+    export_schema_with_title(&schema_for!(SettlementData), &out_dir, "SettlementData");
+
+    // responses
+    export_schema_with_title(&schema_for!(ConfigResponse), &out_dir, "ConfigResponse");
+    export_schema_with_title(
+        &schema_for!(ContractMetaResponse),
+        &out_dir,
+        "ContractMetaResponse",
+    );
+    export_schema(&schema_for!(CounterOfferHistoryResponse), &out_dir);
+    export_schema(&schema_for!(AdminLogResponse), &out_dir);
+    export_schema(&schema_for!(CounterpartiesResponse), &out_dir);
+    export_schema(&schema_for!(DenomsResponse), &out_dir);
+    export_schema(&schema_for!(DigestResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(CreationRecordResponse),
+        &out_dir,
+        "CreationRecordResponse",
+    );
+    export_schema(&schema_for!(IntrinsicValueResponse), &out_dir);
+    export_schema(&schema_for!(PaymentAssessmentResponse), &out_dir);
+    export_schema(&schema_for!(OwnershipStatsResponse), &out_dir);
+    export_schema(&schema_for!(PreviewExerciseMessagesResponse), &out_dir);
+    export_schema(&schema_for!(PrivateMemoResponse), &out_dir);
+    export_schema(&schema_for!(ReconcileResponse), &out_dir);
+    export_schema(&schema_for!(SignedMetadataResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(StatusCodeResponse),
+        &out_dir,
+        "StatusCodeResponse",
+    );
+    export_schema(&schema_for!(MinExpiryDistanceResponse), &out_dir);
+    export_schema(&schema_for!(StorageLayoutResponse), &out_dir);
+}
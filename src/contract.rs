@@ -1,16 +1,57 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, BankMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, to_vec, Addr, BankMsg, Binary, Coin, ContractInfoResponse, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, Event, Fraction, MessageInfo, Order, OverflowError, OverflowOperation, Reply,
+    Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, WasmMsg, WasmQuery,
+};
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use ripemd160::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, CONFIG};
+use crate::fees::{apply_bps, remainder_after_bps, BPS_DENOMINATOR};
+use crate::helpers::secure_delete;
+use crate::msg::{
+    AdminLogEntry, AdminLogResponse, ConfigResponse, CounterOfferHistoryResponse,
+    CounterpartiesResponse, CreatorShare, DenomsResponse, DigestResponse, ExecuteMsg,
+    InstantiateMsg, IntrinsicValueResponse, Leg, MinExpiryDistanceResponse, OwnershipStatsResponse,
+    PaymentAssessmentResponse, PaymentStatus, PreviewExerciseMessagesResponse, PrivateMemoResponse,
+    QueryMsg, ReconcileResponse, ReminderExecuteMsg, RouterExecuteMsg, SignedMetadata,
+    SignedMetadataResponse, StatusCodeResponse, StorageLayoutResponse, STATUS_ACTIVE,
+    STATUS_BURNED, STATUS_CANCELLED, STATUS_EXECUTED, STATUS_EXPIRED,
+};
+use crate::state::{
+    AdminAction, ContractMeta, CounterOfferChange, CreationRecord, CreatorShareRecord, Settlement,
+    SettlementKind, SignedMetadataRecord, State, ADMIN_LOG, ADMIN_LOG_COUNT, BLOCKLIST, CONFIG,
+    CONFIG_KEY, CONTRACT_META, COUNTER_OFFER_HISTORY, CREATION_RECORD, OWNERSHIP_STATS,
+    PRIVATE_MEMO, PRIVATE_MEMO_KEY, SETTLEMENT,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:simple-option";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const CALLBACK_REPLY_ID: u64 = 1;
+
+// an option must be written at least this many blocks before it expires
+pub const MIN_EXPIRY_DISTANCE: u64 = 1000;
+
+// the owner can get at most half of the collateral when the option expires
+pub const MAX_EXPIRY_SPLIT_BPS: u64 = 5000;
+
+// only meant to absorb rounding differences
+pub const MAX_PAYMENT_TOLERANCE_BPS: u64 = 10;
+
+// older counter_offer changes are dropped, to bound storage
+pub const MAX_COUNTER_OFFER_HISTORY: usize = 20;
+
+// page size of the AdminLog query
+pub const DEFAULT_ADMIN_LOG_LIMIT: u32 = 10;
+pub const MAX_ADMIN_LOG_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -30,20 +71,132 @@ pub fn instantiate(
             expired: msg.expires,
         });
     }
+    validate_expiry_distance(&env, msg.expires)?;
+    if let Some(expected) = &msg.expected_collateral {
+        let (expected, got) = (normalize_coins(expected), normalize_coins(&info.funds));
+        if expected != got {
+            return Err(ContractError::CollateralMismatch { expected, got });
+        }
+    }
+    // a zero tip would only produce an empty send
+    let exercise_tip = msg.exercise_tip.clone().filter(|tip| !tip.amount.is_zero());
+    let collateral = match &exercise_tip {
+        Some(tip) => subtract_coins(&info.funds, std::slice::from_ref(tip))?,
+        None => info.funds.clone(),
+    };
+    if msg.single_denom_mode {
+        validate_single_denom(&collateral, &msg.counter_offer)?;
+    }
+    if let Some(bps) = msg.expiry_split_bps {
+        if bps > MAX_EXPIRY_SPLIT_BPS {
+            return Err(ContractError::ExpirySplitTooLarge {
+                max_bps: MAX_EXPIRY_SPLIT_BPS,
+                given_bps: bps,
+            });
+        }
+    }
+    if msg.payment_tolerance_bps > MAX_PAYMENT_TOLERANCE_BPS {
+        return Err(ContractError::PaymentToleranceTooLarge {
+            max_bps: MAX_PAYMENT_TOLERANCE_BPS,
+            given_bps: msg.payment_tolerance_bps,
+        });
+    }
+
+    // a contract creating the option for a user hands it to them right away
+    let creator = match &msg.on_behalf_of {
+        Some(user) => deps.api.addr_validate(user)?,
+        None => info.sender.clone(),
+    };
 
-    let state = State {
-        creator: info.sender.clone(),
-        owner: info.sender.clone(), 
-        collateral: info.funds,
-         //collateral is the funds sent by the contract creator. 
-        counter_offer: msg.counter_offer,
+    // a broker can write the option straight to a client
+    let owner = match &msg.owner {
+        Some(owner) => deps.api.addr_validate(owner)?,
+        None => creator.clone(),
+    };
+
+    let creator_shares = msg
+        .creator_shares
+        .clone()
+        .map(|shares| validate_creator_shares(deps.as_ref(), shares))
+        .transpose()?;
+
+    let signed_metadata = msg
+        .signed_metadata
+        .clone()
+        .map(|metadata| verify_signed_metadata(deps.as_ref(), &creator, metadata))
+        .transpose()?;
+
+    let record = CreationRecord {
+        msg: msg.clone(),
+        sender: info.sender.clone(),
+        funds: info.funds.clone(),
+        height: env.block.height,
+        time: env.block.time.seconds(),
+    };
+    let mut state = State {
+        creator,
+        owner: owner.clone(),
+        collateral,
+        //collateral is the funds sent by the contract creator, less the exercise tip
+        counter_offer: msg.counter_offer.clone(),
         expires: msg.expires,
+        settlement_router: msg
+            .settlement_router
+            .map(|router| deps.api.addr_validate(&router))
+            .transpose()?,
+        min_hold_blocks: msg.min_hold_blocks,
+        acquired_at_height: env.block.height,
+        seq: 0,
+        single_denom_mode: msg.single_denom_mode,
+        signed_metadata,
+        expiry_split_bps: msg.expiry_split_bps,
+        skip_self_sends: msg.skip_self_sends,
+        warn_before_burn_blocks: msg.warn_before_burn_blocks,
+        payment_tolerance_bps: msg.payment_tolerance_bps,
+        funding_allowlist: msg
+            .funding_allowlist
+            .map(|allowlist| {
+                allowlist
+                    .iter()
+                    .map(|funder| deps.api.addr_validate(funder))
+                    .collect::<StdResult<Vec<_>>>()
+            })
+            .transpose()?,
+        reminder_target: msg
+            .reminder_target
+            .as_ref()
+            .map(|target| deps.api.addr_validate(target))
+            .transpose()?,
+        reminder_window_blocks: msg.reminder_window_blocks,
+        reminded: false,
+        exercise_tip,
+        creator_shares,
+        renounce_returns_to_creator: msg.renounce_returns_to_creator,
+    };
+    bump_seq(&mut state);
+
+    let meta = ContractMeta {
+        deployer: info.sender,
+        deploy_block: env.block.height,
+        deploy_time: env.block.time.seconds(),
+        code_checksum: None,
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     CONFIG.save(deps.storage, &state)?;
+    CONTRACT_META.save(deps.storage, &meta)?;
+    CREATION_RECORD.save(deps.storage, &record)?;
 
-    Ok(Response::default())
+    let mut res = Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("seq", state.seq.to_string());
+    if msg.on_behalf_of.is_some() {
+        res = res.add_attribute("on_behalf_of", &state.creator);
+    }
+    if owner != state.creator {
+        res = res.add_event(Event::new("transfer").add_attribute("owner", owner));
+    }
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -55,14 +208,42 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Transfer { recipient } => execute_transfer(deps, env, info, recipient),
-        ExecuteMsg::Execute {} => execute_execute(deps, env, info),
+        ExecuteMsg::Execute { deadline_height } => {
+            execute_execute(deps, env, info, deadline_height)
+        }
         ExecuteMsg::Burn {} => execute_burn(deps, env, info),
+        ExecuteMsg::ExerciseWithCallback {
+            callback_contract,
+            callback_msg,
+        } => execute_exercise_with_callback(deps, env, info, callback_contract, callback_msg),
+        ExecuteMsg::Roll {
+            new_counter_offer,
+            new_expires,
+        } => execute_roll(deps, env, info, new_counter_offer, new_expires),
+        ExecuteMsg::UpdateCodeChecksum { checksum } => {
+            execute_update_code_checksum(deps, env, info, checksum)
+        }
+        ExecuteMsg::ReclaimExcessFunds { denom } => {
+            execute_reclaim_excess_funds(deps, env, info, denom)
+        }
+        ExecuteMsg::Gift { amount } => execute_gift(deps, env, info, amount),
+        ExecuteMsg::SetPrivateMemo { memo } => execute_set_private_memo(deps, info, memo),
+        ExecuteMsg::Renounce {} => execute_renounce(deps, env, info),
+        ExecuteMsg::Noop {} => Err(ContractError::UnsupportedAction {}),
+        ExecuteMsg::TriggerExpiryReminder {} => execute_trigger_expiry_reminder(deps, env),
+        ExecuteMsg::UpdateBlocklist { add, remove } => {
+            execute_update_blocklist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::Amend {
+            counter_offer,
+            expires,
+        } => execute_amend(deps, env, info, counter_offer, expires),
     }
 }
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
 ) -> Result<Response, ContractError> {
@@ -71,233 +252,4612 @@ pub fn execute_transfer(
     if info.sender != state.owner {
         return Err(ContractError::Unauthorized {});
     }
+    // ensure the owner has held the option for at least 'min_hold_blocks'
+    let available_at = state
+        .acquired_at_height
+        .saturating_add(state.min_hold_blocks);
+    if env.block.height < available_at {
+        return Err(ContractError::MinHoldNotMet { available_at });
+    }
     // set new owner on state and save it to the contract state 
-    state.owner = deps.api.addr_validate(&recipient)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    // transferring to the current owner would change nothing
+    if recipient_addr == state.owner {
+        return Err(ContractError::AlreadyOwner {});
+    }
+    ensure_not_blocked(deps.storage, [recipient_addr.as_str()])?;
+    record_hold(deps.storage, &state, &env)?;
+    state.owner = recipient_addr;
+    state.acquired_at_height = env.block.height;
+    bump_seq(&mut state);
     CONFIG.save(deps.storage, &state)?;
 
     let res =
     //add the response to the cosmos sdk event logs
-        Response::new().add_attributes([("action", "transfer"), ("owner", recipient.as_str())]);
+        Response::new().add_attributes([
+            ("action", "transfer"),
+            ("owner", recipient.as_str()),
+            ("seq", &state.seq.to_string()),
+        ]);
     Ok(res)
 }
 
-pub fn execute_execute(
+pub fn execute_renounce(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    // ensure msg sender is the owner
-    let state = CONFIG.load(deps.storage)?;
-    if info.sender != state.owner {
+    let mut state = load_unsettled(deps.storage)?;
+    // the creator cannot renounce to themselves
+    if info.sender != state.owner || state.owner == state.creator {
         return Err(ContractError::Unauthorized {});
     }
-    // ensure the option is not expired by checking if the current block height is greater than or equal to the 'expires' block height set in the contract state. If the option is expired, throw an error 
-    if env.block.height >= state.expires {
-        return Err(ContractError::OptionExpired {
-            expired: state.expires,
-        });
+    record_hold(deps.storage, &state, &env)?;
+    if state.renounce_returns_to_creator {
+        return close_renounced(deps.storage, &env, state);
     }
-    // ensure sending proper counter_offer
-    if info.funds != state.counter_offer {
-        return Err(ContractError::CounterOfferMismatch {
-            offer: info.funds,
-            counter_offer: state.counter_offer,
+    state.owner = state.creator.clone();
+    state.acquired_at_height = env.block.height;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    let res = Response::new()
+        .add_attribute("action", "renounce")
+        .add_attribute("owner", &state.owner)
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+// declines the option in one step, settling it like a burn with all of the collateral and the
+// tip going to the creator
+fn close_renounced(
+    storage: &mut dyn Storage,
+    env: &Env,
+    mut state: State,
+) -> Result<Response, ContractError> {
+    bump_seq(&mut state);
+    let mut legs = creator_legs(&state, &state.collateral)?;
+    if let Some(tip) = &state.exercise_tip {
+        legs.push(Leg {
+            to: state.creator.to_string(),
+            amount: vec![tip.clone()],
         });
     }
-    // release counter_offer to creator
-    let mut res = Response::new();
-    res = res.add_message(BankMsg::Send {
-        to_address: state.creator.to_string(),
-        amount: state.counter_offer,
-    });
-    // release collateral to sender
-    res = res.add_message(BankMsg::Send {
-        //BankMsg refers to the message types of the bank module. It defines a method for sending coins from one account to another account.
-        to_address: state.owner.to_string(),
-        amount: state.collateral,
-    });
+    ensure_not_blocked(storage, legs.iter().map(|leg| leg.to.as_str()))?;
+    let msgs = settlement_msgs(&state.settlement_router, legs)?;
 
-    // delete the option
-    CONFIG.remove(deps.storage);
+    // delete the option, remembering how it ended
+    close_option(storage, env, &state, SettlementKind::Renounced)?;
 
-    res = res.add_attribute("action", "execute");
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "renounce")
+        .add_attribute("option", env.contract.address.as_str())
+        .add_attribute("seq", state.seq.to_string())
+        .add_attributes(coin_count_attributes(&state))
+        .set_data(to_binary(&state)?);
     Ok(res)
 }
 
-pub fn execute_burn(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    // ensure option is expired
-    let state = CONFIG.load(deps.storage)?;
-    if env.block.height < state.expires {
-        return Err(ContractError::OptionNotExpired {
-            expires: state.expires,
-        });
+pub fn execute_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deadline_height: Option<u64>,
+) -> Result<Response, ContractError> {
+    if matches!(deadline_height, Some(deadline) if env.block.height > deadline) {
+        return Err(ContractError::ExerciseDeadlinePassed {});
+    }
+    let (mut state, exercise) = build_exercise(deps.storage, &env, &info)?;
+    bump_seq(&mut state);
+    let mut res = Response::new().add_messages(exercise.messages);
+    if !exercise.shortfall.is_empty() {
+        res = res.add_attribute("payment_shortfall", coins_to_string(&exercise.shortfall));
     }
 
-    // release collateral to creator. Since the option has expired, the collateral is returned to the owner. 
-    let mut res = Response::new();
-    res = res.add_message(BankMsg::Send {
-        to_address: state.creator.to_string(),
-        amount: state.collateral,
-    });
-
-    // delete the option
-    CONFIG.remove(deps.storage);
+    // delete the option, remembering how it ended
+    close_option(deps.storage, &env, &state, SettlementKind::Executed)?;
 
-    res = res.add_attribute("action", "burn");
+    // the record is gone now, so hand the final terms back to the caller
+    res = res
+        .add_attribute("action", "execute")
+        .add_attribute("option", env.contract.address)
+        .add_attribute("seq", state.seq.to_string())
+        .add_attributes(coin_count_attributes(&state))
+        .set_data(to_binary(&state)?);
     Ok(res)
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+// what a successful Execute sends, and by how much the payment fell short of the counter_offer
+struct Exercise {
+    messages: Vec<CosmosMsg>,
+    shortfall: Vec<Coin>,
+}
+
+// All checks and messages of Execute, without changing any state. Shared with the
+// PreviewExerciseMessages query so the preview cannot diverge from the real thing.
+// with an allowlist, only listed funders exercise (on behalf of the owner), otherwise the owner
+fn ensure_may_exercise(state: &State, sender: &Addr) -> Result<(), ContractError> {
+    match &state.funding_allowlist {
+        Some(allowlist) if !allowlist.contains(sender) => Err(ContractError::FunderNotAllowed {}),
+        Some(_) => Ok(()),
+        None if sender != &state.owner => Err(ContractError::Unauthorized {}),
+        None => Ok(()),
     }
 }
 
-fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let state = CONFIG.load(deps.storage)?;
-    Ok(state)
+fn build_exercise(
+    storage: &dyn Storage,
+    env: &Env,
+    info: &MessageInfo,
+) -> Result<(State, Exercise), ContractError> {
+    let state = load_unsettled(storage)?;
+    // the creator exercising their own option would pay the counter_offer to themselves,
+    // so with 'skip_self_sends' they pay nothing and only get the collateral back.
+    // Co-writers are always paid their shares.
+    let self_exercise = state.skip_self_sends
+        && state.creator_shares.is_none()
+        && info.sender == state.creator
+        && state.owner == state.creator;
+    let counter_offer = if self_exercise {
+        vec![]
+    } else {
+        state.counter_offer.clone()
+    };
+    ensure_may_exercise(&state, &info.sender)?;
+    // validated at instantiation to be at most MAX_PAYMENT_TOLERANCE_BPS
+    let tolerance_bps = u16::try_from(state.payment_tolerance_bps)
+        .map_err(|_| StdError::generic_err("invalid payment tolerance"))?;
+    let shortfall = validate_exercise(&state, env, info, &counter_offer, tolerance_bps)?;
+    // release counter_offer to creator and collateral to sender.
+    // The creator gets exactly what was paid, absorbing any shortfall within the tolerance
+    let mut legs = vec![];
+    if !self_exercise {
+        // zero amounts attached to the payment are not passed on
+        let paid: Vec<Coin> = info
+            .funds
+            .iter()
+            .filter(|c| !c.amount.is_zero())
+            .cloned()
+            .collect();
+        legs.extend(creator_legs(&state, &paid)?);
+    }
+    if !(self_exercise && state.collateral.is_empty()) {
+        legs.push(Leg {
+            to: state.owner.to_string(),
+            amount: state.collateral.clone(),
+        });
+    }
+    if let Some(tip) = &state.exercise_tip {
+        legs.push(Leg {
+            to: info.sender.to_string(),
+            amount: vec![tip.clone()],
+        });
+    }
+    ensure_not_blocked(storage, legs.iter().map(|leg| leg.to.as_str()))?;
+    let messages = settlement_msgs(&state.settlement_router, legs)?;
+    Ok((
+        state,
+        Exercise {
+            messages,
+            shortfall,
+        },
+    ))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{attr, coins, CosmosMsg};
+pub fn execute_exercise_with_callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback_contract: String,
+    callback_msg: Binary,
+) -> Result<Response, ContractError> {
+    let mut state = load_unsettled(deps.storage)?;
+    // the caller picks where the collateral goes, so only the owner may do this
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    // and, with an allowlist, only if they are listed as well
+    ensure_may_exercise(&state, &info.sender)?;
+    validate_exercise(&state, &env, &info, &state.counter_offer, 0)?;
+    bump_seq(&mut state);
+    let callback_contract = deps.api.addr_validate(&callback_contract)?;
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
+    // release counter_offer to creator (and the tip to the owner) as usual
+    let mut legs = creator_legs(&state, &state.counter_offer)?;
+    if let Some(tip) = &state.exercise_tip {
+        legs.push(Leg {
+            to: info.sender.to_string(),
+            amount: vec![tip.clone()],
+        });
+    }
+    let recipients = legs.iter().map(|leg| leg.to.as_str());
+    ensure_not_blocked(deps.storage, recipients.chain([callback_contract.as_str()]))?;
+    let mut res = Response::new().add_messages(settlement_msgs(&state.settlement_router, legs)?);
+    // but hand the collateral straight to the callback contract, which can use it in the same tx
+    res = res.add_submessage(SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: callback_contract.to_string(),
+            msg: callback_msg,
+            funds: state.collateral.clone(),
+        },
+        CALLBACK_REPLY_ID,
+    ));
 
-        let msg = InstantiateMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
-        };
-        let info = mock_info("creator", &coins(1, "BTC"));
+    // delete the option, remembering how it ended
+    close_option(deps.storage, &env, &state, SettlementKind::Executed)?;
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    res = res
+        .add_attribute("action", "exercise_with_callback")
+        .add_attribute("option", env.contract.address)
+        .add_attribute("seq", state.seq.to_string())
+        .add_attribute("callback_contract", callback_contract)
+        .add_attributes(coin_count_attributes(&state))
+        .set_data(to_binary(&state)?);
+    Ok(res)
+}
 
-        // it worked, let's query the state
-        let res = query_config(deps.as_ref()).unwrap();
-        assert_eq!(100_000, res.expires);
-        assert_eq!("creator", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
-        assert_eq!(coins(1, "BTC"), res.collateral);
-        assert_eq!(coins(40, "ETH"), res.counter_offer);
-        println!("yo!");
-        
+// the creator must have signed the document hash themselves, with the key their address is derived from
+fn verify_signed_metadata(
+    deps: Deps,
+    creator: &Addr,
+    metadata: SignedMetadata,
+) -> Result<SignedMetadataRecord, ContractError> {
+    let signer = deps.api.addr_validate(&metadata.signer)?;
+    if &signer != creator {
+        return Err(ContractError::InvalidMetadataSignature {});
+    }
+    // on chains deriving addresses some other way the key never matches, so nothing is recorded
+    let key_address = pubkey_to_address(&metadata.public_key)?;
+    if deps.api.addr_canonicalize(creator.as_str())?.as_slice() != key_address.as_slice() {
+        return Err(ContractError::InvalidMetadataSignature {});
+    }
+    let valid = deps
+        .api
+        .secp256k1_verify(
+            &metadata.content_hash,
+            &metadata.signature,
+            &metadata.public_key,
+        )
+        .map_err(|_| ContractError::InvalidMetadataSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidMetadataSignature {});
     }
+    Ok(SignedMetadataRecord {
+        content_hash: metadata.content_hash,
+        signature: metadata.signature,
+        public_key: metadata.public_key,
+        signer,
+    })
+}
 
-    #[test]
-    fn transfer() {
-        let mut deps = mock_dependencies();
+// Cosmos SDK account address of a secp256k1 key: ripemd160(sha256(compressed key))
+fn pubkey_to_address(public_key: &[u8]) -> Result<Vec<u8>, ContractError> {
+    let compressed = match public_key {
+        [0x02 | 0x03, ..] if public_key.len() == 33 => public_key.to_vec(),
+        [0x04, point @ ..] if point.len() == 64 => {
+            let mut compressed = vec![0x02 | (point[63] & 1)];
+            compressed.extend_from_slice(&point[..32]);
+            compressed
+        }
+        _ => return Err(ContractError::InvalidMetadataSignature {}),
+    };
+    Ok(Ripemd160::digest(&Sha256::digest(&compressed)).to_vec())
+}
 
-        let msg = InstantiateMsg {
-            counter_offer: coins(40, "ETH"),
-            expires: 100_000,
+fn validate_creator_shares(
+    deps: Deps,
+    shares: Vec<CreatorShare>,
+) -> Result<Vec<CreatorShareRecord>, ContractError> {
+    let total: u32 = shares.iter().map(|share| u32::from(share.bps)).sum();
+    if total != u32::from(BPS_DENOMINATOR) || shares.iter().any(|share| share.bps == 0) {
+        return Err(ContractError::InvalidShares {});
+    }
+    shares
+        .into_iter()
+        .map(|share| {
+            Ok(CreatorShareRecord {
+                address: deps.api.addr_validate(&share.address)?,
+                bps: share.bps,
+            })
+        })
+        .collect()
+}
+
+// pays 'amount' to the creator, or splits it between the creator shares
+fn creator_legs(state: &State, amount: &[Coin]) -> Result<Vec<Leg>, ContractError> {
+    let shares = match &state.creator_shares {
+        Some(shares) => shares,
+        None => {
+            return Ok(vec![Leg {
+                to: state.creator.to_string(),
+                amount: amount.to_vec(),
+            }])
+        }
+    };
+    let mut remaining = amount.to_vec();
+    let mut legs = vec![];
+    for (i, share) in shares.iter().enumerate() {
+        // the last share gets whatever is left, so rounding never strands anything
+        let part = if i + 1 == shares.len() {
+            std::mem::take(&mut remaining)
+        } else {
+            let mut part = vec![];
+            for c in amount {
+                let share_amount = apply_bps(c.amount, share.bps)?;
+                if !share_amount.is_zero() {
+                    part.push(Coin {
+                        denom: c.denom.clone(),
+                        amount: share_amount,
+                    });
+                }
+            }
+            remaining = subtract_coins(&remaining, &part)?;
+            part
         };
-        let info = mock_info("creator", &coins(1, "BTC"));
+        if !part.is_empty() {
+            legs.push(Leg {
+                to: share.address.to_string(),
+                amount: part,
+            });
+        }
+    }
+    Ok(legs)
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+// 'expires' must already be in the future
+fn validate_expiry_distance(env: &Env, expires: u64) -> Result<(), ContractError> {
+    // an option that never expires could never be burned, locking the collateral for good
+    if expires == u64::MAX {
+        return Err(ContractError::ExpiryTooFar {});
+    }
+    let given_distance = expires - env.block.height;
+    if given_distance < MIN_EXPIRY_DISTANCE {
+        return Err(ContractError::ExpiryTooSoon {
+            min_blocks_ahead: MIN_EXPIRY_DISTANCE,
+            given_distance,
+        });
+    }
+    Ok(())
+}
 
-        // random cannot transfer
-        let info = mock_info("anyone", &[]);
-        let err =
-            execute_transfer(deps.as_mut(), mock_env(), info, "anyone".to_string()).unwrap_err();
-        match err {
-            ContractError::Unauthorized {} => {}
-            e => panic!("unexpected error: {}", e),
+// a counter_offer given by Amend: at least one coin, each denom once, none of them 0.
+// A free option has to be written as one when it is instantiated
+fn validate_counter_offer(counter_offer: &[Coin]) -> Result<(), ContractError> {
+    if counter_offer.is_empty() {
+        return Err(ContractError::InvalidCounterOffer {});
+    }
+    for (i, c) in counter_offer.iter().enumerate() {
+        if c.amount.is_zero() || counter_offer[..i].iter().any(|prev| prev.denom == c.denom) {
+            return Err(ContractError::InvalidCounterOffer {});
         }
+    }
+    Ok(())
+}
 
-        // owner can transfer
-        let info = mock_info("creator", &[]);
-        let res = execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
-        assert_eq!(res.attributes.len(), 2);
-        assert_eq!(res.attributes[0], attr("action", "transfer"));
+fn validate_single_denom(collateral: &[Coin], counter_offer: &[Coin]) -> Result<(), ContractError> {
+    match (collateral, counter_offer) {
+        ([collateral], [counter_offer]) if collateral.denom != counter_offer.denom => Ok(()),
+        _ => Err(ContractError::RequiresSingleDenom {}),
+    }
+}
 
-        // check updated properly
-        let res = query_config(deps.as_ref()).unwrap();
-        assert_eq!("someone", res.owner.as_str());
-        assert_eq!("creator", res.creator.as_str());
+// the admin of the contract as known to the chain, who can also migrate it
+fn ensure_contract_admin(deps: Deps, env: &Env, sender: &Addr) -> Result<(), ContractError> {
+    let contract_info: ContractInfoResponse = deps.querier.query(
+        &WasmQuery::ContractInfo {
+            contract_addr: env.contract.address.to_string(),
+        }
+        .into(),
+    )?;
+    if contract_info.admin.as_deref() != Some(sender.as_str()) {
+        return Err(ContractError::Unauthorized {});
     }
+    Ok(())
+}
 
-    #[test]
-    fn execute() {
-        let mut deps = mock_dependencies();
+fn ensure_not_blocked<'a>(
+    storage: &dyn Storage,
+    recipients: impl IntoIterator<Item = &'a str>,
+) -> Result<(), ContractError> {
+    let blocklist = BLOCKLIST.may_load(storage)?.unwrap_or_default();
+    for recipient in recipients {
+        if blocklist
+            .iter()
+            .any(|blocked| blocked.as_str() == recipient)
+        {
+            return Err(ContractError::BlockedAddress {});
+        }
+    }
+    Ok(())
+}
 
-        let amount = coins(40, "ETH");
-        let collateral = coins(1, "BTC");
-        let expires = 100_000;
-        let msg = InstantiateMsg {
-            counter_offer: amount.clone(),
-            expires,
-        };
-        let info = mock_info("creator", &collateral);
+// the current owner's holding ends now
+fn record_hold(storage: &mut dyn Storage, state: &State, env: &Env) -> StdResult<()> {
+    let mut stats = OWNERSHIP_STATS.may_load(storage)?.unwrap_or_default();
+    stats.total_transfers += 1;
+    stats.total_hold_blocks += env.block.height.saturating_sub(state.acquired_at_height);
+    OWNERSHIP_STATS.save(storage, &stats)
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+fn record_counter_offer_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    counter_offer: &[Coin],
+) -> StdResult<()> {
+    let mut history = COUNTER_OFFER_HISTORY.may_load(storage)?.unwrap_or_default();
+    history.push(CounterOfferChange {
+        counter_offer: counter_offer.to_vec(),
+        height: env.block.height,
+    });
+    if history.len() > MAX_COUNTER_OFFER_HISTORY {
+        history.drain(..history.len() - MAX_COUNTER_OFFER_HISTORY);
+    }
+    COUNTER_OFFER_HISTORY.save(storage, &history)
+}
 
-        // set new owner
-        let info = mock_info("creator", &[]);
-        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+fn record_admin_action(
+    storage: &mut dyn Storage,
+    env: &Env,
+    admin: &Addr,
+    kind: &str,
+    msg: &ExecuteMsg,
+) -> StdResult<()> {
+    let id = ADMIN_LOG_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    let action = AdminAction {
+        kind: kind.to_string(),
+        payload_json: String::from_utf8(to_vec(msg)?)?,
+        admin: admin.clone(),
+        height: env.block.height,
+    };
+    ADMIN_LOG.save(storage, id, &action)?;
+    ADMIN_LOG_COUNT.save(storage, &id)
+}
 
-        // random cannot execute
-        let info = mock_info("creator", &amount);
-        let err = execute_execute(deps.as_mut(), mock_env(), info).unwrap_err();
-        match err {
-            ContractError::Unauthorized {} => {}
-            e => panic!("unexpected error: {}", e),
-        }
+// Every state-changing action bumps the sequence number and reports it in its "seq" attribute,
+// so indexers can order the events of an option
+fn bump_seq(state: &mut State) {
+    state.seq += 1;
+}
 
-        // expired cannot execute
-        let info = mock_info("owner", &amount);
-        let mut env = mock_env();
-        env.block.height = 200_000;
-        let err = execute_execute(deps.as_mut(), env, info).unwrap_err();
-        match err {
-            ContractError::OptionExpired { expired } => assert_eq!(expired, expires),
-            e => panic!("unexpected error: {}", e),
+// sizes of the settled option, to correlate with gas usage
+fn coin_count_attributes(state: &State) -> [(&'static str, String); 2] {
+    [
+        ("collateral_coin_count", state.collateral.len().to_string()),
+        (
+            "counter_offer_coin_count",
+            state.counter_offer.len().to_string(),
+        ),
+    ]
+}
+
+// the option for a settlement, telling a late second settlement apart from a missing option
+fn load_unsettled(storage: &dyn Storage) -> Result<State, ContractError> {
+    if let Some(settlement) = SETTLEMENT.may_load(storage)? {
+        return Err(ContractError::AlreadySettled {
+            kind: settlement.kind,
+            height: settlement.height,
+        });
+    }
+    Ok(CONFIG.load(storage)?)
+}
+
+fn close_option(
+    storage: &mut dyn Storage,
+    env: &Env,
+    state: &State,
+    kind: SettlementKind,
+) -> StdResult<()> {
+    let settlement = Settlement {
+        kind,
+        height: env.block.height,
+        seq: state.seq,
+    };
+    SETTLEMENT.save(storage, &settlement)?;
+    secure_delete(storage, CONFIG_KEY.as_bytes());
+    secure_delete(storage, PRIVATE_MEMO_KEY.as_bytes());
+    Ok(())
+}
+
+// shared checks for every way of exercising the option
+// 'counter_offer' is the payment expected from the caller, who must be checked by the handler
+// Payments may fall short of it by up to 'tolerance_bps' per coin, the shortfall is returned.
+fn validate_exercise(
+    state: &State,
+    env: &Env,
+    info: &MessageInfo,
+    counter_offer: &[Coin],
+    tolerance_bps: u16,
+) -> Result<Vec<Coin>, ContractError> {
+    // ensure the option is not expired by checking if the current block height is greater than or equal to the 'expires' block height set in the contract state. If the option is expired, throw an error
+    if env.block.height >= state.expires {
+        return Err(ContractError::OptionExpired {
+            expired: state.expires,
+        });
+    }
+    // ensure sending proper counter_offer
+    match payment_shortfall(&info.funds, counter_offer, tolerance_bps)? {
+        Some(shortfall) => Ok(shortfall),
+        None => Err(ContractError::CounterOfferMismatch {
+            offer: info.funds.clone(),
+            counter_offer: counter_offer.to_vec(),
+        }),
+    }
+}
+
+// how much 'funds' falls short of 'counter_offer', or None if it is not an acceptable payment
+fn payment_shortfall(
+    funds: &[Coin],
+    counter_offer: &[Coin],
+    tolerance_bps: u16,
+) -> Result<Option<Vec<Coin>>, ContractError> {
+    // neither zero amounts nor the order of the coins make a difference
+    let (funds, counter_offer) = (normalize_coins(funds), normalize_coins(counter_offer));
+    if funds == counter_offer {
+        return Ok(Some(vec![]));
+    }
+    if tolerance_bps == 0 || funds.len() != counter_offer.len() {
+        return Ok(None);
+    }
+    let mut shortfall = vec![];
+    for expected in &counter_offer {
+        let paid = match funds.iter().find(|c| c.denom == expected.denom) {
+            Some(paid) => paid.amount,
+            None => return Ok(None),
+        };
+        let min = remainder_after_bps(expected.amount, tolerance_bps)?;
+        if paid > expected.amount || paid < min {
+            return Ok(None);
         }
+        if paid < expected.amount {
+            shortfall.push(Coin {
+                denom: expected.denom.clone(),
+                amount: expected.amount - paid,
+            });
+        }
+    }
+    Ok(Some(shortfall))
+}
 
-        // bad counter_offer cannot execute
-        let msg_offer = coins(39, "ETH");
-        let info = mock_info("owner", &msg_offer);
-        let err = execute_execute(deps.as_mut(), mock_env(), info).unwrap_err();
-        match err {
-            ContractError::CounterOfferMismatch {
-                offer,
-                counter_offer,
-            } => {
-                assert_eq!(msg_offer, offer);
-                assert_eq!(amount, counter_offer);
+// the collateral of an expired option as (owner share, creator share) by 'expiry_split_bps'
+fn expiry_split(state: &State) -> Result<(Vec<Coin>, Vec<Coin>), ContractError> {
+    // validated at instantiation to be at most MAX_EXPIRY_SPLIT_BPS
+    let split_bps = u16::try_from(state.expiry_split_bps.unwrap_or(0))
+        .map_err(|_| StdError::generic_err("invalid expiry split"))?;
+    let (mut owner_share, mut creator_share) = (vec![], vec![]);
+    for c in &state.collateral {
+        let shares = [
+            (&mut owner_share, apply_bps(c.amount, split_bps)?),
+            (
+                &mut creator_share,
+                remainder_after_bps(c.amount, split_bps)?,
+            ),
+        ];
+        for (share, amount) in shares {
+            if !amount.is_zero() {
+                share.push(Coin {
+                    denom: c.denom.clone(),
+                    amount,
+                });
             }
-            e => panic!("unexpected error: {}", e),
         }
+    }
+    Ok((owner_share, creator_share))
+}
 
-        // proper execution
-        let info = mock_info("owner", &amount);
-        let res = execute_execute(deps.as_mut(), mock_env(), info).unwrap();
-        assert_eq!(res.messages.len(), 2);
-        assert_eq!(
-            res.messages[0].msg,
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: "creator".into(),
-                amount,
+pub fn execute_burn(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    // ensure option is expired
+    let mut state = load_unsettled(deps.storage)?;
+    if env.block.height < state.expires {
+        return Err(ContractError::OptionNotExpired {
+            expires: state.expires,
+        });
+    }
+    // right after expiry only the parties may burn, anyone else just leaves a warning.
+    // This succeeds without burning, as a failing transaction would drop the event
+    let open_at = state.expires.saturating_add(state.warn_before_burn_blocks);
+    if env.block.height < open_at && info.sender != state.owner && info.sender != state.creator {
+        let warning = Event::new("burn_warning")
+            .add_attribute("expires", state.expires.to_string())
+            .add_attribute("open_at", open_at.to_string())
+            .add_attribute("seq", state.seq.to_string());
+        return Ok(Response::new()
+            .add_event(warning)
+            .add_attribute("action", "burn_warning")
+            .add_attribute("seq", state.seq.to_string()));
+    }
+    bump_seq(&mut state);
+
+    // release collateral to creator. Since the option has expired, the collateral is returned to the owner. 
+    // With an expiry split, the owner gets their share of it first.
+    let (owner_share, creator_share) = expiry_split(&state)?;
+    let mut legs = creator_legs(&state, &creator_share)?;
+    let mut res = Response::new();
+    if !owner_share.is_empty() {
+        res = res
+            .add_attribute("creator_share", coins_to_string(&creator_share))
+            .add_attribute("owner_share", coins_to_string(&owner_share));
+        legs.push(Leg {
+            to: state.owner.to_string(),
+            amount: owner_share,
+        });
+    }
+    // nobody exercised, so the creator keeps the tip
+    if let Some(tip) = &state.exercise_tip {
+        legs.push(Leg {
+            to: state.creator.to_string(),
+            amount: vec![tip.clone()],
+        });
+    }
+    ensure_not_blocked(deps.storage, legs.iter().map(|leg| leg.to.as_str()))?;
+    res = res.add_messages(settlement_msgs(&state.settlement_router, legs)?);
+
+    // delete the option, remembering how it ended
+    close_option(deps.storage, &env, &state, SettlementKind::Burned)?;
+
+    res = res
+        .add_attribute("action", "burn")
+        .add_attribute("option", env.contract.address)
+        .add_attribute("seq", state.seq.to_string())
+        .add_attributes(coin_count_attributes(&state))
+        .set_data(to_binary(&state)?);
+    Ok(res)
+}
+
+pub fn execute_roll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_counter_offer: Vec<Coin>,
+    new_expires: u64,
+) -> Result<Response, ContractError> {
+    // once expired the collateral belongs to the creator again, so only they may reuse it
+    let mut state = load_unsettled(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.height < state.expires {
+        return Err(ContractError::OptionNotExpired {
+            expires: state.expires,
+        });
+    }
+    if new_expires <= env.block.height {
+        return Err(ContractError::OptionExpired {
+            expired: new_expires,
+        });
+    }
+    validate_expiry_distance(&env, new_expires)?;
+    validate_counter_offer(&new_counter_offer)?;
+    if state.single_denom_mode {
+        validate_single_denom(&state.collateral, &new_counter_offer)?;
+    }
+
+    // the owner of the expired option is paid their expiry split as on burn,
+    // the new option keeps the rest of the collateral
+    let mut res = Response::new();
+    if state.owner != state.creator {
+        let (owner_share, creator_share) = expiry_split(&state)?;
+        if !owner_share.is_empty() {
+            let legs = vec![Leg {
+                to: state.owner.to_string(),
+                amount: owner_share.clone(),
+            }];
+            ensure_not_blocked(deps.storage, legs.iter().map(|leg| leg.to.as_str()))?;
+            res = res
+                .add_messages(settlement_msgs(&state.settlement_router, legs)?)
+                .add_attribute("owner_share", coins_to_string(&owner_share));
+            state.collateral = creator_share;
+        }
+    }
+
+    // close the old option and write a fresh one with the same collateral.
+    // The owner's holding ends here, as with Transfer or Renounce
+    if state.owner != state.creator {
+        record_hold(deps.storage, &state, &env)?;
+    }
+    state.owner = state.creator.clone();
+    record_counter_offer_change(deps.storage, &env, &new_counter_offer)?;
+    state.counter_offer = new_counter_offer;
+    state.expires = new_expires;
+    state.acquired_at_height = env.block.height;
+    state.reminded = false;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    res = res
+        .add_attribute("action", "roll")
+        .add_attribute("expires", new_expires.to_string())
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+pub fn execute_gift(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    amount: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG.load(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    if amount.is_empty() || amount.iter().any(|c| c.amount.is_zero()) {
+        return Err(ContractError::EmptyGift {});
+    }
+    state.collateral = subtract_coins(&state.collateral, &amount)?;
+    // an option without collateral has nothing left to exercise, and in single denom mode
+    // would no longer hold its one collateral coin
+    if state.collateral.is_empty() {
+        return Err(ContractError::GiftEmptiesCollateral {});
+    }
+    let legs = vec![Leg {
+        to: state.owner.to_string(),
+        amount,
+    }];
+    ensure_not_blocked(deps.storage, legs.iter().map(|leg| leg.to.as_str()))?;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    // paid out like any settlement, through the router if there is one
+    let res = Response::new()
+        .add_messages(settlement_msgs(&state.settlement_router, legs)?)
+        .add_attribute("action", "gift")
+        .add_attribute("owner", state.owner)
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+pub fn execute_set_private_memo(
+    deps: DepsMut,
+    info: MessageInfo,
+    memo: String,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG.load(deps.storage)?;
+    if info.sender != state.creator && info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    PRIVATE_MEMO.save(deps.storage, &memo)?;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    // the memo itself is not put into the events
+    let res = Response::new()
+        .add_attribute("action", "set_private_memo")
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+pub fn execute_amend(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    counter_offer: Vec<Coin>,
+    expires: u64,
+) -> Result<Response, ContractError> {
+    let mut state = load_unsettled(deps.storage)?;
+    if info.sender != state.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    // the terms are only the creator's own business while nobody else ever held the option,
+    // even if it was renounced or transferred back to them since
+    let stats = OWNERSHIP_STATS.may_load(deps.storage)?.unwrap_or_default();
+    if state.owner != state.creator || stats.total_transfers > 0 {
+        return Err(ContractError::AlreadyTransferred {});
+    }
+    if expires <= env.block.height {
+        return Err(ContractError::OptionExpired { expired: expires });
+    }
+    validate_expiry_distance(&env, expires)?;
+    validate_counter_offer(&counter_offer)?;
+    if state.single_denom_mode {
+        validate_single_denom(&state.collateral, &counter_offer)?;
+    }
+
+    record_counter_offer_change(deps.storage, &env, &counter_offer)?;
+    state.counter_offer = counter_offer;
+    state.expires = expires;
+    state.reminded = false;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    let res = Response::new()
+        .add_attribute("action", "amend")
+        .add_attribute("expires", expires.to_string())
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+pub fn execute_trigger_expiry_reminder(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut state = CONFIG.load(deps.storage)?;
+    let target = match &state.reminder_target {
+        Some(target) => target.clone(),
+        None => return Err(ContractError::NoReminderTarget {}),
+    };
+    if env.block.height >= state.expires {
+        return Err(ContractError::OptionExpired {
+            expired: state.expires,
+        });
+    }
+    let window_opens = state.expires.saturating_sub(state.reminder_window_blocks);
+    if env.block.height < window_opens {
+        return Err(ContractError::ReminderNotDue { window_opens });
+    }
+    if state.reminded {
+        return Err(ContractError::AlreadyReminded {});
+    }
+    state.reminded = true;
+    bump_seq(&mut state);
+    CONFIG.save(deps.storage, &state)?;
+
+    // a failing target reverts the trigger, so the reminder can be retried
+    let msg = WasmMsg::Execute {
+        contract_addr: target.to_string(),
+        msg: to_binary(&ReminderExecuteMsg::ExpiryReminder {
+            expires: state.expires,
+        })?,
+        funds: vec![],
+    };
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute("action", "trigger_expiry_reminder")
+        .add_attribute("seq", state.seq.to_string());
+    Ok(res)
+}
+
+pub fn execute_update_code_checksum(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    checksum: Binary,
+) -> Result<Response, ContractError> {
+    // only the admin that can migrate the contract knows which code it runs
+    ensure_contract_admin(deps.as_ref(), &env, &info.sender)?;
+
+    let mut meta = CONTRACT_META.load(deps.storage)?;
+    meta.code_checksum = Some(checksum.clone());
+    CONTRACT_META.save(deps.storage, &meta)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_code_checksum",
+        &ExecuteMsg::UpdateCodeChecksum {
+            checksum: checksum.clone(),
+        },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_code_checksum")
+        .add_attribute("checksum", checksum.to_base64());
+    Ok(res)
+}
+
+pub fn execute_update_blocklist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_contract_admin(deps.as_ref(), &env, &info.sender)?;
+
+    let mut blocklist = BLOCKLIST.may_load(deps.storage)?.unwrap_or_default();
+    for address in &remove {
+        let address = deps.api.addr_validate(address)?;
+        blocklist.retain(|blocked| blocked != &address);
+    }
+    for address in &add {
+        let address = deps.api.addr_validate(address)?;
+        if !blocklist.contains(&address) {
+            blocklist.push(address);
+        }
+    }
+    BLOCKLIST.save(deps.storage, &blocklist)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_blocklist",
+        &ExecuteMsg::UpdateBlocklist { add, remove },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_blocklist")
+        .add_attribute("blocked_count", blocklist.len().to_string());
+    Ok(res)
+}
+
+pub fn execute_reclaim_excess_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    // the collateral and tip of a live option are locked, everything else held in 'denom' is excess
+    let locked = CONFIG
+        .may_load(deps.storage)?
+        .map(|state| {
+            state
+                .collateral
+                .iter()
+                .chain(state.exercise_tip.iter())
+                .filter(|c| c.denom == denom)
+                .map(|c| c.amount)
+                .sum()
+        })
+        .unwrap_or_default();
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    // checked so an accounting bug can never release locked collateral
+    let excess = balance.checked_sub(locked).map_err(StdError::from)?;
+    if excess.is_zero() {
+        return Err(ContractError::NothingToReclaim { denom });
+    }
+    ensure_not_blocked(deps.storage, [info.sender.as_str()])?;
+
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount: excess,
+            }],
+        })
+        .add_attribute("action", "reclaim_excess_funds")
+        .add_attribute("denom", denom)
+        .add_attribute("amount", excess.to_string());
+    Ok(res)
+}
+
+// Without a router every leg is paid with a direct BankMsg::Send. With a router, all funds go to the
+// router in a single WasmMsg::Execute; if the router call fails, the whole settlement is reverted.
+fn settlement_msgs(router: &Option<Addr>, legs: Vec<Leg>) -> StdResult<Vec<CosmosMsg>> {
+    // e.g. the creator's leg of a free exercise, the bank module rejects empty sends
+    let legs: Vec<Leg> = legs
+        .into_iter()
+        .filter(|leg| !leg.amount.is_empty())
+        .collect();
+    let router = match router {
+        Some(router) => router,
+        None => {
+            //BankMsg refers to the message types of the bank module. It defines a method for sending coins from one account to another account.
+            return Ok(legs
+                .into_iter()
+                .map(|leg| {
+                    BankMsg::Send {
+                        to_address: leg.to,
+                        amount: leg.amount,
+                    }
+                    .into()
+                })
+                .collect());
+        }
+    };
+
+    let funds = sum_coins(legs.iter().flat_map(|leg| leg.amount.iter()));
+    let msg = WasmMsg::Execute {
+        contract_addr: router.to_string(),
+        msg: to_binary(&RouterExecuteMsg::RouteSettlement { legs })?,
+        funds,
+    };
+    Ok(vec![msg.into()])
+}
+
+// removes 'amount' from 'coins', dropping denoms that reach zero
+fn subtract_coins(coins: &[Coin], amount: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    let mut remaining = coins.to_vec();
+    for coin in amount {
+        let have = remaining
+            .iter_mut()
+            .find(|c| c.denom == coin.denom)
+            .ok_or(ContractError::InsufficientCollateral {})?;
+        have.amount = have
+            .amount
+            .checked_sub(coin.amount)
+            .map_err(|_| ContractError::InsufficientCollateral {})?;
+    }
+    remaining.retain(|c| !c.amount.is_zero());
+    Ok(remaining)
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(Coin::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// merge coins of the same denom, sorted by denom as the bank module expects
+fn sum_coins<'a>(coins: impl Iterator<Item = &'a Coin>) -> Vec<Coin> {
+    let mut total: Vec<Coin> = vec![];
+    for coin in coins {
+        match total.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(c) => c.amount += coin.amount,
+            None => total.push(coin.clone()),
+        }
+    }
+    total.sort_by(|a, b| a.denom.cmp(&b.denom));
+    total
+}
+
+// merged and sorted like sum_coins, without zero amounts
+fn normalize_coins(coins: &[Coin]) -> Vec<Coin> {
+    let mut coins = sum_coins(coins.iter());
+    coins.retain(|c| !c.amount.is_zero());
+    coins
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        // a failing callback reverts the whole exercise, but with a clear error
+        (CALLBACK_REPLY_ID, SubMsgResult::Err(reason)) => {
+            Err(ContractError::CallbackFailed { reason })
+        }
+        (id, _) => Err(StdError::generic_err(format!("unexpected reply id {}", id)).into()),
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::IntrinsicValue { price } => to_binary(&query_intrinsic_value(deps, price)?),
+        QueryMsg::Counterparties {} => to_binary(&query_counterparties(deps)?),
+        QueryMsg::ContractMeta {} => to_binary(&CONTRACT_META.load(deps.storage)?),
+        QueryMsg::CreationRecord {} => to_binary(&CREATION_RECORD.load(deps.storage)?),
+        QueryMsg::SignedMetadata {} => to_binary(&SignedMetadataResponse {
+            signed_metadata: CONFIG.load(deps.storage)?.signed_metadata,
+        }),
+        QueryMsg::StatusCode {} => to_binary(&query_status_code(deps, env)?),
+        QueryMsg::Reconcile {} => to_binary(&query_reconcile(deps, env)?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::PaymentAssessment { funds } => to_binary(&query_payment_assessment(deps, funds)?),
+        QueryMsg::PrivateMemo { as_address } => to_binary(&query_private_memo(deps, as_address)?),
+        QueryMsg::MinExpiryDistance {} => to_binary(&MinExpiryDistanceResponse {
+            min_expiry_distance: MIN_EXPIRY_DISTANCE,
+        }),
+        QueryMsg::PreviewExerciseMessages { sender, funds } => {
+            to_binary(&query_preview_exercise_messages(deps, env, sender, funds)?)
+        }
+        QueryMsg::OwnershipStats {} => to_binary(&query_ownership_stats(deps)?),
+        QueryMsg::StorageLayout {} => to_binary(&StorageLayoutResponse {
+            config_key: CONFIG_KEY.to_string(),
+            options_namespace: None,
+            counter_key: None,
+        }),
+        QueryMsg::Digest {} => to_binary(&query_digest(deps)?),
+        QueryMsg::CounterOfferHistory { limit } => {
+            to_binary(&query_counter_offer_history(deps, limit)?)
+        }
+        QueryMsg::AdminLog { start_after, limit } => {
+            to_binary(&query_admin_log(deps, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    Ok(state)
+}
+
+fn query_preview_exercise_messages(
+    deps: Deps,
+    env: Env,
+    sender: String,
+    funds: Vec<Coin>,
+) -> StdResult<PreviewExerciseMessagesResponse> {
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&sender)?,
+        funds,
+    };
+    let res = match build_exercise(deps.storage, &env, &info) {
+        Ok((_, exercise)) => PreviewExerciseMessagesResponse {
+            messages: exercise.messages,
+            error: None,
+            error_code: None,
+        },
+        Err(err) => PreviewExerciseMessagesResponse {
+            messages: vec![],
+            error: Some(err.to_string()),
+            error_code: Some(err.error_code()),
+        },
+    };
+    Ok(res)
+}
+
+fn query_counter_offer_history(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<CounterOfferHistoryResponse> {
+    let mut changes = COUNTER_OFFER_HISTORY
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if let Some(limit) = limit {
+        changes.drain(..changes.len().saturating_sub(limit as usize));
+    }
+    Ok(CounterOfferHistoryResponse { changes })
+}
+
+fn query_admin_log(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AdminLogResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_ADMIN_LOG_LIMIT)
+        .min(MAX_ADMIN_LOG_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let entries = ADMIN_LOG
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, action)| AdminLogEntry { id, action }))
+        .collect::<StdResult<_>>()?;
+    Ok(AdminLogResponse { entries })
+}
+
+fn query_ownership_stats(deps: Deps) -> StdResult<OwnershipStatsResponse> {
+    let stats = OWNERSHIP_STATS.may_load(deps.storage)?.unwrap_or_default();
+    let avg_hold_blocks = stats
+        .total_hold_blocks
+        .checked_div(stats.total_transfers)
+        .unwrap_or(0);
+    Ok(OwnershipStatsResponse {
+        total_transfers: stats.total_transfers,
+        avg_hold_blocks,
+    })
+}
+
+// the option terms that make up the digest, serialized in field order
+#[derive(Serialize)]
+struct DigestTerms<'a> {
+    creator: &'a Addr,
+    collateral: Vec<Coin>,
+    counter_offer: Vec<Coin>,
+    expires: u64,
+}
+
+fn query_digest(deps: Deps) -> StdResult<DigestResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let terms = DigestTerms {
+        creator: &state.creator,
+        collateral: normalize_coins(&state.collateral),
+        counter_offer: normalize_coins(&state.counter_offer),
+        expires: state.expires,
+    };
+    let digest = Sha256::digest(&to_vec(&terms)?);
+    Ok(DigestResponse {
+        digest: Binary::from(digest.as_slice()),
+    })
+}
+
+fn query_status_code(deps: Deps, env: Env) -> StdResult<StatusCodeResponse> {
+    if let Some(state) = CONFIG.may_load(deps.storage)? {
+        if env.block.height >= state.expires {
+            return Ok(STATUS_EXPIRED);
+        }
+        return Ok(STATUS_ACTIVE);
+    }
+    let settlement = SETTLEMENT.load(deps.storage)?;
+    Ok(match settlement.kind {
+        SettlementKind::Executed => STATUS_EXECUTED,
+        SettlementKind::Burned => STATUS_BURNED,
+        SettlementKind::Renounced => STATUS_CANCELLED,
+    })
+}
+
+fn query_reconcile(deps: Deps, env: Env) -> StdResult<ReconcileResponse> {
+    let recorded = CONFIG
+        .may_load(deps.storage)?
+        .map(|state| sum_coins(state.collateral.iter().chain(state.exercise_tip.iter())))
+        .unwrap_or_default();
+    let held = sum_coins(
+        deps.querier
+            .query_all_balances(&env.contract.address)?
+            .iter(),
+    );
+
+    let amount_of = |coins: &[Coin], denom: &str| {
+        coins
+            .iter()
+            .find(|c| c.denom == denom)
+            .map(|c| c.amount)
+            .unwrap_or_default()
+    };
+    let mut denoms: Vec<&str> = recorded
+        .iter()
+        .chain(held.iter())
+        .map(|c| c.denom.as_str())
+        .collect();
+    denoms.sort_unstable();
+    denoms.dedup();
+    let discrepancies = denoms
+        .into_iter()
+        .filter_map(|denom| {
+            let (recorded, held) = (amount_of(&recorded, denom), amount_of(&held, denom));
+            let difference = recorded.max(held) - recorded.min(held);
+            (!difference.is_zero()).then(|| Coin {
+                denom: denom.to_string(),
+                amount: difference,
+            })
+        })
+        .collect();
+
+    Ok(ReconcileResponse {
+        recorded,
+        held,
+        discrepancies,
+    })
+}
+
+fn query_private_memo(deps: Deps, as_address: String) -> StdResult<PrivateMemoResponse> {
+    let memo = match CONFIG.may_load(deps.storage)? {
+        Some(state) if as_address == state.creator || as_address == state.owner => {
+            PRIVATE_MEMO.may_load(deps.storage)?
+        }
+        _ => None,
+    };
+    Ok(PrivateMemoResponse { memo })
+}
+
+fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let denoms = |coins: &[Coin]| {
+        let mut denoms: Vec<String> = coins.iter().map(|c| c.denom.clone()).collect();
+        denoms.sort_unstable();
+        denoms.dedup();
+        denoms
+    };
+    Ok(DenomsResponse {
+        collateral_denoms: denoms(&state.collateral),
+        counter_offer_denoms: denoms(&state.counter_offer),
+    })
+}
+
+fn query_payment_assessment(deps: Deps, funds: Vec<Coin>) -> StdResult<PaymentAssessmentResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let paid = sum_coins(funds.iter());
+    let expected = sum_coins(state.counter_offer.iter());
+    // per denom, what 'coins' has beyond 'other'
+    let excess = |coins: &[Coin], other: &[Coin]| -> Vec<Coin> {
+        coins
+            .iter()
+            .filter_map(|c| {
+                let other = other
+                    .iter()
+                    .find(|o| o.denom == c.denom)
+                    .map(|o| o.amount)
+                    .unwrap_or_default();
+                (c.amount > other).then(|| Coin {
+                    denom: c.denom.clone(),
+                    amount: c.amount - other,
+                })
+            })
+            .collect()
+    };
+    let shortfall = excess(&expected, &paid);
+    let surplus = excess(&paid, &expected);
+    let status = if !shortfall.is_empty() {
+        PaymentStatus::Under { shortfall }
+    } else if !surplus.is_empty() {
+        PaymentStatus::Over { surplus }
+    } else {
+        PaymentStatus::Exact {}
+    };
+
+    // validated at instantiation to be at most MAX_PAYMENT_TOLERANCE_BPS
+    let tolerance_bps = u16::try_from(state.payment_tolerance_bps)
+        .map_err(|_| StdError::generic_err("invalid payment tolerance"))?;
+    let accepted = payment_shortfall(&funds, &state.counter_offer, tolerance_bps)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        .is_some();
+    Ok(PaymentAssessmentResponse { status, accepted })
+}
+
+fn query_counterparties(deps: Deps) -> StdResult<CounterpartiesResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    Ok(CounterpartiesResponse {
+        writer: state.creator,
+        holder: state.owner,
+        // no protocol fees are charged by this contract
+        fee_collector: None,
+        payee: state.settlement_router,
+    })
+}
+
+fn query_intrinsic_value(deps: Deps, price: Decimal) -> StdResult<IntrinsicValueResponse> {
+    let state = CONFIG.load(deps.storage)?;
+    let (collateral, counter_offer) = match (&state.collateral[..], &state.counter_offer[..]) {
+        ([collateral], [counter_offer]) => (collateral, counter_offer),
+        _ => {
+            return Err(StdError::generic_err(
+                "intrinsic value requires single-denom collateral and counter_offer",
+            ))
+        }
+    };
+    // (price - strike) * collateral, where strike = counter_offer / collateral
+    let market_value = collateral
+        .amount
+        .checked_multiply_ratio(price.numerator(), price.denominator())
+        .map_err(|_| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Mul,
+                collateral.amount,
+                price,
+            ))
+        })?;
+    // out of the money it is worth nothing
+    let value = market_value
+        .checked_sub(counter_offer.amount)
+        .unwrap_or_default();
+    Ok(IntrinsicValueResponse { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{ContractMetaResponse, CreationRecordResponse, SettlementData};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MockApi,
+        MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        attr, coin, coins, from_binary, from_slice, Api, CanonicalAddr, ContractResult, CosmosMsg,
+        Empty, OwnedDeps, RecoverPubkeyError, SystemError, SystemResult, Uint128,
+        VerificationError,
+    };
+    use std::marker::PhantomData;
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query_config(deps.as_ref()).unwrap();
+        assert_eq!(100_000, res.expires);
+        assert_eq!("creator", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+        assert_eq!(coins(1, "BTC"), res.collateral);
+        assert_eq!(coins(40, "ETH"), res.counter_offer);
+        println!("yo!");
+        
+    }
+
+    #[test]
+    fn transfer() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // random cannot transfer
+        let info = mock_info("anyone", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), mock_env(), info, "anyone".to_string()).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // owner can transfer
+        let info = mock_info("creator", &[]);
+        let res = execute_transfer(deps.as_mut(), mock_env(), info, "someone".to_string()).unwrap();
+        assert_eq!(res.attributes.len(), 3);
+        assert_eq!(res.attributes[0], attr("action", "transfer"));
+
+        // check updated properly
+        let res = query_config(deps.as_ref()).unwrap();
+        assert_eq!("someone", res.owner.as_str());
+        assert_eq!("creator", res.creator.as_str());
+    }
+
+    #[test]
+    fn execute() {
+        let mut deps = mock_dependencies();
+
+        let amount = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let expires = 100_000;
+        let msg = InstantiateMsg {
+            counter_offer: amount.clone(),
+            expires,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+
+        // we can just call .unwrap() to assert this was a success
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // set new owner
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // random cannot execute
+        let info = mock_info("creator", &amount);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // expired cannot execute
+        let info = mock_info("owner", &amount);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let err = execute_execute(deps.as_mut(), env, info, None).unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, expires),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // bad counter_offer cannot execute
+        let msg_offer = coins(39, "ETH");
+        let info = mock_info("owner", &msg_offer);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+        match err {
+            ContractError::CounterOfferMismatch {
+                offer,
+                counter_offer,
+            } => {
+                assert_eq!(msg_offer, offer);
+                assert_eq!(amount, counter_offer);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // proper execution
+        let info = mock_info("owner", &amount);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount,
+            })
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount: collateral,
+            })
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref()).unwrap_err();
+        assert_eq!(deps.storage.get(CONFIG_KEY.as_bytes()), None);
+    }
+
+    #[test]
+    fn execute_with_router() {
+        let mut deps = mock_dependencies();
+
+        let counter_offer = vec![coin(40, "ETH"), coin(5, "BTC")];
+        let collateral = coins(1, "BTC");
+        let msg = InstantiateMsg {
+            counter_offer: counter_offer.clone(),
+            expires: 100_000,
+            settlement_router: Some("router".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        let info = mock_info("owner", &counter_offer);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let (contract_addr, msg, funds) = match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => (contract_addr, msg, funds),
+            m => panic!("unexpected message: {:?}", m),
+        };
+        assert_eq!(contract_addr, "router");
+        // funds attached are the sum of all legs
+        assert_eq!(funds, &vec![coin(6, "BTC"), coin(40, "ETH")]);
+
+        let RouterExecuteMsg::RouteSettlement { legs } = from_binary(msg).unwrap();
+        assert_eq!(
+            legs,
+            vec![
+                Leg {
+                    to: "creator".to_string(),
+                    amount: counter_offer,
+                },
+                Leg {
+                    to: "owner".to_string(),
+                    amount: collateral,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn burn_with_router() {
+        let mut deps = mock_dependencies();
+
+        let collateral = coins(1, "BTC");
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            settlement_router: Some("router".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router".into(),
+                msg: to_binary(&RouterExecuteMsg::RouteSettlement {
+                    legs: vec![Leg {
+                        to: "creator".to_string(),
+                        amount: collateral.clone(),
+                    }],
+                })
+                .unwrap(),
+                funds: collateral,
+            })
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref()).unwrap_err();
+        assert_eq!(deps.storage.get(CONFIG_KEY.as_bytes()), None);
+    }
+
+    #[test]
+    fn transfer_respects_min_hold() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            min_hold_blocks: 100,
+            ..Default::default()
+        };
+        let mut env = mock_env();
+        env.block.height = 1_000;
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // too soon after creation
+        env.block.height = 1_099;
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), env.clone(), info, "someone".to_string()).unwrap_err();
+        match err {
+            ContractError::MinHoldNotMet { available_at } => assert_eq!(available_at, 1_100),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // allowed once held long enough
+        env.block.height = 1_100;
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), env.clone(), info, "someone".to_string()).unwrap();
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().acquired_at_height,
+            1_100
+        );
+
+        // the new owner has to wait again
+        env.block.height = 1_150;
+        let info = mock_info("someone", &[]);
+        let err =
+            execute_transfer(deps.as_mut(), env.clone(), info, "other".to_string()).unwrap_err();
+        match err {
+            ContractError::MinHoldNotMet { available_at } => assert_eq!(available_at, 1_200),
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 1_200;
+        let info = mock_info("someone", &[]);
+        let _ = execute_transfer(deps.as_mut(), env, info, "other".to_string()).unwrap();
+    }
+
+    #[test]
+    fn reclaim_excess_funds() {
+        // 1 BTC is the collateral, the rest was sent by accident
+        let mut deps = mock_dependencies_with_balance(&[coin(3, "BTC"), coin(7, "ATOM")]);
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // collateral denom only returns what is above the collateral
+        let info = mock_info("anyone", &[]);
+        let res = execute_reclaim_excess_funds(deps.as_mut(), mock_env(), info, "BTC".to_string())
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "anyone".into(),
+                amount: coins(2, "BTC"),
+            })
+        );
+
+        // other denoms are entirely excess
+        let info = mock_info("anyone", &[]);
+        let res = execute_reclaim_excess_funds(deps.as_mut(), mock_env(), info, "ATOM".to_string())
+            .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "anyone".into(),
+                amount: coins(7, "ATOM"),
+            })
+        );
+
+        // nothing held beyond the collateral
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, vec![coin(1, "BTC")]);
+        let info = mock_info("anyone", &[]);
+        let err = execute_reclaim_excess_funds(deps.as_mut(), mock_env(), info, "BTC".to_string())
+            .unwrap_err();
+        match err {
+            ContractError::NothingToReclaim { denom } => assert_eq!(denom, "BTC"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn intrinsic_value() {
+        let mut deps = mock_dependencies();
+
+        // strike of 40 ETH per BTC for 2 BTC
+        let msg = InstantiateMsg {
+            counter_offer: coins(80, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(2, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // in the money: (45 - 40) * 2
+        let res = query_intrinsic_value(deps.as_ref(), Decimal::percent(4_500)).unwrap();
+        assert_eq!(res.value, Uint128::new(10));
+
+        // at and out of the money
+        let res = query_intrinsic_value(deps.as_ref(), Decimal::percent(4_000)).unwrap();
+        assert_eq!(res.value, Uint128::zero());
+        let res = query_intrinsic_value(deps.as_ref(), Decimal::percent(3_000)).unwrap();
+        assert_eq!(res.value, Uint128::zero());
+    }
+
+    #[test]
+    fn intrinsic_value_rejects_multi_denom() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let err = query_intrinsic_value(deps.as_ref(), Decimal::one()).unwrap_err();
+        assert!(matches!(err, StdError::GenericErr { .. }));
+    }
+
+    #[test]
+    fn intrinsic_value_overflow_is_an_error() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(u128::MAX, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let err = query_intrinsic_value(deps.as_ref(), Decimal::percent(200)).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        // still fine up to the largest amount
+        let res = query_intrinsic_value(deps.as_ref(), Decimal::one()).unwrap();
+        assert_eq!(res.value, Uint128::MAX - Uint128::new(40));
+    }
+
+    #[test]
+    fn settlement_returns_final_state() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let before = query_config(deps.as_ref()).unwrap();
+
+        // exercise
+        let info_exec = mock_info("creator", &coins(40, "ETH"));
+        let res = execute_execute(deps.as_mut(), mock_env(), info_exec, None).unwrap();
+        let data: SettlementData = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            State {
+                seq: before.seq + 1,
+                ..before
+            }
+        );
+        assert_eq!(res.attributes[1], attr("option", MOCK_CONTRACT_ADDR));
+
+        // burn
+        let mut deps = mock_dependencies();
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let before = query_config(deps.as_ref()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        let data: SettlementData = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            State {
+                seq: before.seq + 1,
+                ..before
+            }
+        );
+        assert_eq!(res.attributes[1], attr("option", MOCK_CONTRACT_ADDR));
+    }
+
+    #[test]
+    fn roll() {
+        let mut deps = mock_dependencies();
+
+        let collateral = coins(1, "BTC");
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        // cannot roll before expiry
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_roll(deps.as_mut(), mock_env(), info, coins(50, "ETH"), 300_000).unwrap_err();
+        match err {
+            ContractError::OptionNotExpired { expires } => assert_eq!(expires, 100_000),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+
+        // only the creator can roll
+        let info = mock_info("owner", &[]);
+        let err =
+            execute_roll(deps.as_mut(), env.clone(), info, coins(50, "ETH"), 300_000).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // new expiry must be in the future
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_roll(deps.as_mut(), env.clone(), info, coins(50, "ETH"), 200_000).unwrap_err();
+        match err {
+            ContractError::OptionExpired { expired } => assert_eq!(expired, 200_000),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // rolling cannot turn the option into a free one, or one with malformed terms
+        for counter_offer in [
+            vec![],
+            vec![coin(0, "ETH")],
+            vec![coin(50, "ETH"), coin(10, "ETH")],
+        ] {
+            let info = mock_info("creator", &[]);
+            let err =
+                execute_roll(deps.as_mut(), env.clone(), info, counter_offer, 300_000).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidCounterOffer {}));
+        }
+
+        // proper roll moves no funds
+        let info = mock_info("creator", &[]);
+        let res =
+            execute_roll(deps.as_mut(), env.clone(), info, coins(50, "ETH"), 300_000).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.attributes[0], attr("action", "roll"));
+
+        // the new option carries over the collateral and is active again
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!("creator", state.owner.as_str());
+        assert_eq!(collateral, state.collateral);
+        assert_eq!(coins(50, "ETH"), state.counter_offer);
+        assert_eq!(300_000, state.expires);
+        let info = mock_info("creator", &coins(50, "ETH"));
+        let _ = execute_execute(deps.as_mut(), env.clone(), info, None).unwrap();
+
+        // a settled option cannot be rolled
+        env.block.height = 400_000;
+        let info = mock_info("creator", &[]);
+        let err = execute_roll(deps.as_mut(), env, info, coins(50, "ETH"), 500_000).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadySettled { .. }));
+    }
+
+    #[test]
+    fn exercise_with_callback() {
+        let mut deps = mock_dependencies();
+
+        let amount = coins(40, "ETH");
+        let collateral = coins(1, "BTC");
+        let msg = InstantiateMsg {
+            counter_offer: amount.clone(),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &collateral);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        let callback_msg = Binary::from(br#"{"repay":{}}"#);
+
+        // random cannot exercise
+        let info = mock_info("anyone", &amount);
+        let err = execute_exercise_with_callback(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "vault".to_string(),
+            callback_msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // collateral goes to the callback contract instead of the owner
+        let info = mock_info("owner", &amount);
+        let res = execute_exercise_with_callback(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            "vault".to_string(),
+            callback_msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount,
+            })
+        );
+        assert_eq!(
+            res.messages[1],
+            SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: "vault".into(),
+                    msg: callback_msg,
+                    funds: collateral,
+                },
+                CALLBACK_REPLY_ID,
+            )
+        );
+
+        // check deleted
+        let _ = query_config(deps.as_ref()).unwrap_err();
+
+        // a failed callback surfaces as CallbackFailed
+        let err = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: CALLBACK_REPLY_ID,
+                result: SubMsgResult::Err("insufficient funds".to_string()),
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::CallbackFailed { reason } => assert_eq!(reason, "insufficient funds"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    // Whether a message changes the option, and so has to bump its seq. Every variant that does
+    // must also be run through execute_bumps_seq in seq_covers_every_action.
+    fn changes_option(msg: &ExecuteMsg) -> bool {
+        match msg {
+            ExecuteMsg::Transfer { .. }
+            | ExecuteMsg::Execute { .. }
+            | ExecuteMsg::Burn {}
+            | ExecuteMsg::ExerciseWithCallback { .. }
+            | ExecuteMsg::Roll { .. }
+            | ExecuteMsg::Gift { .. }
+            | ExecuteMsg::SetPrivateMemo { .. }
+            | ExecuteMsg::Renounce {}
+            | ExecuteMsg::TriggerExpiryReminder {}
+            | ExecuteMsg::Amend { .. } => true,
+            // contract level, or rejected: the option itself stays the same
+            ExecuteMsg::UpdateCodeChecksum { .. }
+            | ExecuteMsg::ReclaimExcessFunds { .. }
+            | ExecuteMsg::UpdateBlocklist { .. }
+            | ExecuteMsg::Noop {} => false,
+        }
+    }
+
+    // Runs any execute message and checks that it bumped the option's sequence by exactly one,
+    // both in the "seq" attribute and in the stored (or settled) state.
+    fn execute_bumps_seq(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Response {
+        assert!(changes_option(&msg), "{:?} does not change the option", msg);
+        let before = CONFIG.load(&deps.storage).unwrap().seq;
+        let res = super::execute(deps.as_mut(), env, info, msg).unwrap();
+        let seq_attr = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "seq")
+            .expect("every option event must report its seq");
+        assert_eq!(seq_attr.value, (before + 1).to_string());
+        // settled options report their final seq in the response data instead
+        let after = match &res.data {
+            Some(data) => from_binary::<SettlementData>(data).unwrap().seq,
+            None => CONFIG.load(&deps.storage).unwrap().seq,
+        };
+        assert_eq!(after, before + 1);
+        res
+    }
+
+    #[test]
+    fn seq_increments_without_gaps() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[1], attr("seq", "1"));
+        assert_eq!(query_config(deps.as_ref()).unwrap().seq, 1);
+
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+        };
+        execute_bumps_seq(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &[]),
+            transfer("owner"),
+        );
+        execute_bumps_seq(
+            &mut deps,
+            mock_env(),
+            mock_info("owner", &[]),
+            transfer("creator"),
+        );
+
+        // expire and roll
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        execute_bumps_seq(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::Roll {
+                new_counter_offer: coins(40, "ETH"),
+                new_expires: 300_000,
+            },
+        );
+        assert_eq!(query_config(deps.as_ref()).unwrap().seq, 4);
+
+        // failed actions do not consume a sequence number
+        let err = super::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            transfer("anyone"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        assert_eq!(query_config(deps.as_ref()).unwrap().seq, 4);
+
+        let res = execute_bumps_seq(
+            &mut deps,
+            env,
+            mock_info("creator", &coins(40, "ETH")),
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        );
+        let data: SettlementData = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(data.seq, 5);
+    }
+
+    #[test]
+    fn seq_covers_every_action() {
+        let new_option = |owner: &str, renounce_returns_to_creator| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                owner: Some(owner.to_string()),
+                reminder_target: Some("reminders".to_string()),
+                reminder_window_blocks: 100_000,
+                renounce_returns_to_creator,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(10, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let mut expired = mock_env();
+        expired.block.height = 200_000;
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+        };
+
+        // everything that keeps the option open, then exercise it
+        let mut deps = new_option("creator", false);
+        for (sender, msg) in [
+            (
+                "creator",
+                ExecuteMsg::SetPrivateMemo {
+                    memo: "memo".to_string(),
+                },
+            ),
+            (
+                "creator",
+                ExecuteMsg::Amend {
+                    counter_offer: coins(40, "ETH"),
+                    expires: 100_000,
+                },
+            ),
+            ("creator", transfer("owner")),
+            (
+                "creator",
+                ExecuteMsg::Gift {
+                    amount: coins(1, "BTC"),
+                },
+            ),
+            ("keeper", ExecuteMsg::TriggerExpiryReminder {}),
+            ("owner", ExecuteMsg::Renounce {}),
+            ("creator", transfer("owner")),
+        ] {
+            execute_bumps_seq(&mut deps, mock_env(), mock_info(sender, &[]), msg);
+        }
+        let msg = ExecuteMsg::Execute {
+            deadline_height: None,
+        };
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_bumps_seq(&mut deps, mock_env(), info, msg);
+
+        // and every other way an option ends or starts over
+        let mut deps = new_option("owner", false);
+        let msg = ExecuteMsg::ExerciseWithCallback {
+            callback_contract: "callback".to_string(),
+            callback_msg: Binary::from(b"{}".to_vec()),
+        };
+        let info = mock_info("owner", &coins(40, "ETH"));
+        execute_bumps_seq(&mut deps, mock_env(), info, msg);
+
+        let mut deps = new_option("owner", false);
+        let info = mock_info("owner", &[]);
+        execute_bumps_seq(&mut deps, expired.clone(), info, ExecuteMsg::Burn {});
+
+        let mut deps = new_option("owner", false);
+        let msg = ExecuteMsg::Roll {
+            new_counter_offer: coins(40, "ETH"),
+            new_expires: 300_000,
+        };
+        execute_bumps_seq(&mut deps, expired, mock_info("creator", &[]), msg);
+
+        let mut deps = new_option("owner", true);
+        let info = mock_info("owner", &[]);
+        execute_bumps_seq(&mut deps, mock_env(), info, ExecuteMsg::Renounce {});
+    }
+
+    #[test]
+    fn expiry_distance() {
+        let height = mock_env().block.height;
+        let instantiate_expiring = |expires| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg)
+        };
+
+        // below minimum
+        let err = instantiate_expiring(height + MIN_EXPIRY_DISTANCE - 1).unwrap_err();
+        match err {
+            ContractError::ExpiryTooSoon {
+                min_blocks_ahead,
+                given_distance,
+            } => {
+                assert_eq!(min_blocks_ahead, MIN_EXPIRY_DISTANCE);
+                assert_eq!(given_distance, MIN_EXPIRY_DISTANCE - 1);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // exactly at and well above minimum
+        instantiate_expiring(height + MIN_EXPIRY_DISTANCE).unwrap();
+        instantiate_expiring(height + 100 * MIN_EXPIRY_DISTANCE).unwrap();
+
+        // never expiring
+        let err = instantiate_expiring(u64::MAX).unwrap_err();
+        assert!(matches!(err, ContractError::ExpiryTooFar {}));
+        instantiate_expiring(u64::MAX - 1).unwrap();
+
+        let deps = mock_dependencies();
+        let res: MinExpiryDistanceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::MinExpiryDistance {}).unwrap())
+                .unwrap();
+        assert_eq!(res.min_expiry_distance, MIN_EXPIRY_DISTANCE);
+    }
+
+    #[test]
+    fn roll_respects_expiry_distance() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("creator", &[]);
+        let err =
+            execute_roll(deps.as_mut(), env.clone(), info, coins(40, "ETH"), 200_999).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::ExpiryTooSoon {
+                given_distance: 999,
+                ..
+            }
+        ));
+        let info = mock_info("creator", &[]);
+        let _ = execute_roll(deps.as_mut(), env, info, coins(40, "ETH"), 201_000).unwrap();
+    }
+
+    #[test]
+    fn counterparties() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            settlement_router: Some("router".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+
+        let state = query_config(deps.as_ref()).unwrap();
+        let res = query_counterparties(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            CounterpartiesResponse {
+                writer: state.creator,
+                holder: state.owner,
+                fee_collector: None,
+                payee: state.settlement_router,
+            }
+        );
+        assert_eq!(res.payee.unwrap().as_str(), "router");
+    }
+
+    #[test]
+    fn contract_meta() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let env = mock_env();
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let meta: ContractMetaResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ContractMeta {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            meta,
+            ContractMeta {
+                deployer: Addr::unchecked("creator"),
+                deploy_block: env.block.height,
+                deploy_time: env.block.time.seconds(),
+                code_checksum: None,
+            }
+        );
+
+        // the contract admin is known to the chain
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == MOCK_CONTRACT_ADDR => {
+                let mut info = ContractInfoResponse::new(1, "creator");
+                info.admin = Some("admin".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        // only the admin may set the checksum, not even the deployer
+        let checksum = Binary::from(vec![0xab; 32]);
+        let info = mock_info("creator", &[]);
+        let err = execute_update_code_checksum(deps.as_mut(), mock_env(), info, checksum.clone())
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let info = mock_info("admin", &[]);
+        let _ = execute_update_code_checksum(deps.as_mut(), mock_env(), info, checksum.clone())
+            .unwrap();
+        let meta = CONTRACT_META.load(&deps.storage).unwrap();
+        assert_eq!(meta.code_checksum, Some(checksum));
+    }
+
+    #[test]
+    fn single_denom_mode() {
+        let instantiate_with = |collateral: &[Coin], counter_offer: Vec<Coin>| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer,
+                expires: 100_000,
+                single_denom_mode: true,
+                ..Default::default()
+            };
+            let info = mock_info("creator", collateral);
+            instantiate(deps.as_mut(), mock_env(), info, msg).map(|_| deps)
+        };
+
+        // compliant
+        let mut deps = instantiate_with(&coins(1, "BTC"), coins(40, "ETH")).unwrap();
+
+        // multi-denom collateral, multi-denom counter_offer, same denoms, nothing at all
+        for (collateral, counter_offer) in [
+            (vec![coin(1, "BTC"), coin(1, "ATOM")], coins(40, "ETH")),
+            (coins(1, "BTC"), vec![coin(40, "ETH"), coin(1, "ATOM")]),
+            (coins(1, "BTC"), coins(40, "BTC")),
+            (vec![], coins(40, "ETH")),
+        ] {
+            let err = instantiate_with(&collateral, counter_offer).err().unwrap();
+            assert!(matches!(err, ContractError::RequiresSingleDenom {}));
+        }
+
+        // rolling has to keep the option compliant
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("creator", &[]);
+        let err = execute_roll(
+            deps.as_mut(),
+            env,
+            info,
+            vec![coin(40, "ETH"), coin(1, "ATOM")],
+            300_000,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RequiresSingleDenom {}));
+    }
+
+    #[test]
+    fn creation_record_is_immutable() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            min_hold_blocks: 10,
+            ..Default::default()
+        };
+        let env = mock_env();
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+
+        let expected = CreationRecord {
+            msg,
+            sender: Addr::unchecked("creator"),
+            funds: coins(1, "BTC"),
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        };
+        let load = |deps: Deps| -> CreationRecordResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::CreationRecord {}).unwrap()).unwrap()
+        };
+        assert_eq!(load(deps.as_ref()), expected);
+
+        // transfer and roll change the option, never the record
+        let mut env = mock_env();
+        env.block.height += 10;
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), env.clone(), info, "owner".to_string()).unwrap();
+        assert_eq!(load(deps.as_ref()), expected);
+
+        env.block.height = 200_000;
+        let info = mock_info("creator", &[]);
+        let _ = execute_roll(deps.as_mut(), env.clone(), info, coins(50, "ETH"), 300_000).unwrap();
+        assert_eq!(load(deps.as_ref()), expected);
+
+        // and it outlives settlement
+        let info = mock_info("creator", &coins(50, "ETH"));
+        let _ = execute_execute(deps.as_mut(), env, info, None).unwrap();
+        let _ = query_config(deps.as_ref()).unwrap_err();
+        assert_eq!(load(deps.as_ref()), expected);
+    }
+
+    // Addresses are the hex encoded canonical address, like a Cosmos SDK chain without bech32.
+    // Unlike with MockApi, a test can then own the address of a key.
+    #[derive(Clone, Copy, Default)]
+    struct HexApi(MockApi);
+
+    impl Api for HexApi {
+        fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+            let normalized = self.addr_humanize(&self.addr_canonicalize(human)?)?;
+            if human != normalized {
+                return Err(StdError::generic_err(
+                    "Invalid input: address not normalized",
+                ));
+            }
+            Ok(normalized)
+        }
+
+        fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+            (0..human.len())
+                .step_by(2)
+                .map(|i| {
+                    human
+                        .get(i..i + 2)
+                        .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .map(CanonicalAddr::from)
+                .ok_or_else(|| StdError::generic_err("Invalid input: address not hex"))
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            let human: String = canonical
+                .as_slice()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            Ok(Addr::unchecked(human))
+        }
+
+        fn secp256k1_verify(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(message_hash, signature, public_key)
+        }
+
+        fn secp256k1_recover_pubkey(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            recovery_param: u8,
+        ) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0
+                .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+        }
+
+        fn ed25519_verify(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, public_key)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            public_keys: &[&[u8]],
+        ) -> Result<bool, VerificationError> {
+            self.0
+                .ed25519_batch_verify(messages, signatures, public_keys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    fn sign_document(key: &[u8; 32], document: &[u8], signer: &str) -> SignedMetadata {
+        use k256::ecdsa::signature::Signer;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(key).unwrap();
+        // signs the sha256 hash of the document
+        let signature: Signature = signing_key.sign(document);
+        SignedMetadata {
+            content_hash: Sha256::digest(document).into(),
+            signature: Binary::from(signature.as_ref()),
+            public_key: Binary::from(signing_key.verifying_key().to_bytes().as_slice()),
+            signer: signer.to_string(),
+        }
+    }
+
+    #[test]
+    fn signed_metadata() {
+        let instantiate_with = |creator: &str, metadata: SignedMetadata| {
+            let mut deps = OwnedDeps {
+                storage: MockStorage::default(),
+                api: HexApi::default(),
+                querier: MockQuerier::default(),
+                custom_query_type: PhantomData::<Empty>,
+            };
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                signed_metadata: Some(metadata),
+                ..Default::default()
+            };
+            let info = mock_info(creator, &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).map(|_| deps)
+        };
+        let key = [7u8; 32];
+        // ripemd160(sha256(compressed public key of 'key'))
+        let creator = "a3c6b1ee4a49d9f2af3b3802974744fba924164a";
+        let document = b"terms of the option";
+
+        // valid signature by the creator
+        let metadata = sign_document(&key, document, creator);
+        let deps = instantiate_with(creator, metadata.clone()).unwrap();
+        let res: SignedMetadataResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::SignedMetadata {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            res.signed_metadata,
+            Some(SignedMetadataRecord {
+                content_hash: metadata.content_hash,
+                signature: metadata.signature,
+                public_key: metadata.public_key,
+                signer: Addr::unchecked(creator),
+            })
+        );
+
+        // the uncompressed form of the key belongs to the creator as well
+        let mut uncompressed = sign_document(&key, document, creator);
+        use k256::elliptic_curve::sec1::ToEncodedPoint;
+        let signing_key = k256::ecdsa::SigningKey::from_bytes(&key).unwrap();
+        let point = signing_key.verifying_key().to_encoded_point(false);
+        uncompressed.public_key = Binary::from(point.as_bytes());
+        let _ = instantiate_with(creator, uncompressed).unwrap();
+
+        // valid signature, but by a key that is not the creator's
+        let foreign = sign_document(&[9u8; 32], document, creator);
+        let err = instantiate_with(creator, foreign).err().unwrap();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+
+        // signature over a different document
+        let mut tampered = sign_document(&key, document, creator);
+        tampered.content_hash = sign_document(&key, b"other terms", creator).content_hash;
+        let err = instantiate_with(creator, tampered).err().unwrap();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+
+        // malformed signature and key
+        let mut garbage = sign_document(&key, document, creator);
+        garbage.signature = Binary::from(vec![1u8; 10]);
+        let err = instantiate_with(creator, garbage).err().unwrap();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+        let mut garbage = sign_document(&key, document, creator);
+        garbage.public_key = Binary::from(vec![2u8; 10]);
+        let err = instantiate_with(creator, garbage).err().unwrap();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+
+        // signed by someone other than the creator
+        let someone = "0000000000000000000000000000000000000001";
+        let err = instantiate_with(someone, sign_document(&key, document, creator))
+            .err()
+            .unwrap();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+
+        // addresses not derived from the key as on Cosmos SDK chains are never verified
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            signed_metadata: Some(sign_document(&key, document, "creator")),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMetadataSignature {}));
+
+        // without metadata nothing is stored
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().signed_metadata, None);
+    }
+
+    #[test]
+    fn gift() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(10, "BTC"), coin(5, "ATOM")]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only the creator may gift
+        let msg = ExecuteMsg::Gift {
+            amount: coins(1, "BTC"),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // cannot gift more than the collateral, or a denom that is not locked
+        for amount in [coins(11, "BTC"), coins(1, "ETH")] {
+            let msg = ExecuteMsg::Gift { amount };
+            let err = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+                .unwrap_err();
+            assert!(matches!(err, ContractError::InsufficientCollateral {}));
+        }
+        // or nothing at all
+        for amount in [
+            vec![],
+            coins(0, "BTC"),
+            vec![coin(1, "BTC"), coin(0, "ATOM")],
+        ] {
+            let msg = ExecuteMsg::Gift { amount };
+            let err = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+                .unwrap_err();
+            assert!(matches!(err, ContractError::EmptyGift {}));
+        }
+
+        let amount = vec![coin(4, "BTC"), coin(5, "ATOM")];
+        let msg = ExecuteMsg::Gift {
+            amount: amount.clone(),
+        };
+        let res =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".into(),
+                amount,
+            })
+        );
+
+        // the option keeps the rest of the collateral and its terms
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!(state.collateral, coins(6, "BTC"));
+        assert_eq!(state.counter_offer, coins(40, "ETH"));
+        assert_eq!(state.owner, Addr::unchecked("owner"));
+
+        // but cannot give all of it away
+        let msg = ExecuteMsg::Gift {
+            amount: coins(6, "BTC"),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::GiftEmptiesCollateral {}));
+
+        // a single denom option keeps its collateral coin
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            single_denom_mode: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(10, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::Gift {
+            amount: coins(10, "BTC"),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::GiftEmptiesCollateral {}));
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().collateral,
+            coins(10, "BTC")
+        );
+    }
+
+    #[test]
+    fn gift_with_router() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owner: Some("owner".to_string()),
+            settlement_router: Some("router".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(10, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::Gift {
+            amount: coins(4, "BTC"),
+        };
+        let res =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let legs = vec![Leg {
+            to: "owner".to_string(),
+            amount: coins(4, "BTC"),
+        }];
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router".to_string(),
+                msg: to_binary(&RouterExecuteMsg::RouteSettlement { legs }).unwrap(),
+                funds: coins(4, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn burn_with_expiry_split() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            expiry_split_bps: Some(1000),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(15, "BTC"), coin(9, "ATOM")]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        // 10% of 15 is rounded down to 1, 10% of 9 down to nothing
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|m| m.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "creator".into(),
+                    amount: vec![coin(14, "BTC"), coin(9, "ATOM")],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "owner".into(),
+                    amount: coins(1, "BTC"),
+                }),
+            ]
+        );
+        assert!(res
+            .attributes
+            .contains(&attr("creator_share", "14BTC,9ATOM")));
+        assert!(res.attributes.contains(&attr("owner_share", "1BTC")));
+        let settled: SettlementData = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(settled.expiry_split_bps, Some(1000));
+
+        // more than half of the collateral cannot go to the owner
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            expiry_split_bps: Some(5001),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(15, "BTC"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::ExpirySplitTooLarge {
+                max_bps: 5000,
+                given_bps: 5001
+            }
+        ));
+    }
+
+    #[test]
+    fn self_exercise_skips_counter_offer() {
+        let instantiate_with = |skip_self_sends: bool, collateral: &[Coin]| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                skip_self_sends,
+                ..Default::default()
+            };
+            let info = mock_info("creator", collateral);
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        // by default the creator pays themselves
+        let mut deps = instantiate_with(false, &coins(1, "BTC"));
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+
+        // skipped: no payment, only the collateral comes back
+        let mut deps = instantiate_with(true, &coins(1, "BTC"));
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+        assert!(matches!(err, ContractError::CounterOfferMismatch { .. }));
+        let info = mock_info("creator", &[]);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|m| m.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })]
+        );
+        let _ = query_config(deps.as_ref()).unwrap_err();
+
+        // without collateral nothing is sent at all
+        let mut deps = instantiate_with(true, &[]);
+        let info = mock_info("creator", &[]);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        let _ = query_config(deps.as_ref()).unwrap_err();
+
+        // a transferred option is exercised as usual
+        let mut deps = instantiate_with(true, &coins(1, "BTC"));
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(res.messages.len(), 2);
+    }
+
+    #[test]
+    fn funding_allowlist() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            funding_allowlist: Some(vec!["treasury".to_string()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // neither strangers nor the unlisted owner may fund the exercise
+        for funder in ["anyone", "owner"] {
+            let info = mock_info(funder, &coins(40, "ETH"));
+            let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+            assert!(matches!(err, ContractError::FunderNotAllowed {}));
+        }
+        // not even by exercising with a callback
+        let callback = ExecuteMsg::ExerciseWithCallback {
+            callback_contract: "callback".to_string(),
+            callback_msg: Binary::from(b"{}".to_vec()),
+        };
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let err = super::execute(deps.as_mut(), mock_env(), info, callback.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::FunderNotAllowed {}));
+
+        // the treasury pays, the owner gets the collateral
+        let info = mock_info("treasury", &coins(40, "ETH"));
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|m| m.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "creator".into(),
+                    amount: coins(40, "ETH"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "owner".into(),
+                    amount: coins(1, "BTC"),
+                }),
+            ]
+        );
+
+        // a listed owner may still exercise with a callback
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owner: Some("owner".to_string()),
+            funding_allowlist: Some(vec!["treasury".to_string(), "owner".to_string()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let _ = super::execute(deps.as_mut(), mock_env(), info, callback).unwrap();
+    }
+
+    #[test]
+    fn status_code() {
+        let status_at = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let res = query(deps.as_ref(), env, QueryMsg::StatusCode {}).unwrap();
+            from_binary::<StatusCodeResponse>(&res).unwrap()
+        };
+        let new_option = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        let mut deps = new_option();
+        assert_eq!(status_at(&deps, 99_999), STATUS_ACTIVE);
+        assert_eq!(status_at(&deps, 100_000), STATUS_EXPIRED);
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let _ = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(status_at(&deps, 100_000), STATUS_EXECUTED);
+
+        let mut deps = new_option();
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let _ = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        assert_eq!(status_at(&deps, 200_000), STATUS_BURNED);
+        assert_eq!(
+            SETTLEMENT.load(&deps.storage).unwrap(),
+            Settlement {
+                kind: SettlementKind::Burned,
+                height: 200_000,
+                seq: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn instantiate_on_behalf_of() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            on_behalf_of: Some("user".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("router", &coins(1, "BTC"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("on_behalf_of", "user")));
+
+        // the user controls the option, not the router
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!(state.creator, Addr::unchecked("user"));
+        assert_eq!(state.owner, Addr::unchecked("user"));
+        let msg = ExecuteMsg::Transfer {
+            recipient: "router".to_string(),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("router", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // while the record keeps who actually instantiated
+        let record: CreationRecordResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CreationRecord {}).unwrap())
+                .unwrap();
+        assert_eq!(record.sender, Addr::unchecked("router"));
+        assert_eq!(record.msg.on_behalf_of, Some("user".to_string()));
+
+        // without it nothing changes
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes.len(), 2);
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().owner,
+            Addr::unchecked("creator")
+        );
+    }
+
+    #[test]
+    fn reconcile() {
+        let reconcile = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::Reconcile {}).unwrap();
+            from_binary::<ReconcileResponse>(&res).unwrap()
+        };
+
+        // more BTC and some ATOM held than recorded, and less ETH
+        let mut deps =
+            mock_dependencies_with_balance(&[coin(3, "BTC"), coin(7, "ATOM"), coin(1, "ETH")]);
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ETH")]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            reconcile(&deps),
+            ReconcileResponse {
+                recorded: vec![coin(1, "BTC"), coin(5, "ETH")],
+                held: vec![coin(7, "ATOM"), coin(3, "BTC"), coin(1, "ETH")],
+                discrepancies: vec![coin(7, "ATOM"), coin(2, "BTC"), coin(4, "ETH")],
+            }
+        );
+
+        // balanced
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, vec![coin(1, "BTC"), coin(5, "ETH")]);
+        assert_eq!(reconcile(&deps).discrepancies, vec![]);
+
+        // after settlement nothing is recorded anymore
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let _ = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(2, "BTC"));
+        assert_eq!(
+            reconcile(&deps),
+            ReconcileResponse {
+                recorded: vec![],
+                held: coins(2, "BTC"),
+                discrepancies: coins(2, "BTC"),
+            }
+        );
+    }
+
+    #[test]
+    fn burn_warning_window() {
+        let new_option = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                warn_before_burn_blocks: 50,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let msg = ExecuteMsg::Transfer {
+                recipient: "owner".to_string(),
+            };
+            let info = mock_info("creator", &[]);
+            let _ = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+
+        let mut deps = new_option();
+        let mut env = mock_env();
+        env.block.height = 100_049;
+
+        // a stranger only triggers the warning
+        let res = execute_burn(deps.as_mut(), env.clone(), mock_info("anyone", &[])).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "burn_warning");
+        assert_eq!(
+            res.events[0].attributes,
+            vec![
+                attr("expires", "100000"),
+                attr("open_at", "100050"),
+                attr("seq", "2"),
+            ]
+        );
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "burn_warning"), attr("seq", "2")]
+        );
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!(state.seq, 2);
+
+        // the parties can burn in the window
+        for party in ["owner", "creator"] {
+            let mut deps = new_option();
+            let res = execute_burn(deps.as_mut(), env.clone(), mock_info(party, &[])).unwrap();
+            assert_eq!(res.messages.len(), 1);
+        }
+
+        // after the window anyone can
+        env.block.height = 100_050;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(SETTLEMENT.load(&deps.storage).unwrap().seq, 3);
+        assert_eq!(res.events.len(), 0);
+        let _ = query_config(deps.as_ref()).unwrap_err();
+    }
+
+    #[test]
+    fn private_memo() {
+        let memo_for = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, address: &str| {
+            let msg = QueryMsg::PrivateMemo {
+                as_address: address.to_string(),
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<PrivateMemoResponse>(&res).unwrap().memo
+        };
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(memo_for(&deps, "owner"), None);
+
+        // only the parties may set it
+        let msg = ExecuteMsg::SetPrivateMemo {
+            memo: "deal #42".to_string(),
+        };
+        let err = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = super::execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "set_private_memo"), attr("seq", "3")]
+        );
+
+        // and read it
+        assert_eq!(memo_for(&deps, "creator"), Some("deal #42".to_string()));
+        assert_eq!(memo_for(&deps, "owner"), Some("deal #42".to_string()));
+        assert_eq!(memo_for(&deps, "anyone"), None);
+
+        // it is not part of the config
+        let config = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        assert!(!String::from_utf8(config.to_vec())
+            .unwrap()
+            .contains("deal #42"));
+
+        // and is removed with the option
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let _ = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(memo_for(&deps, "owner"), None);
+        assert_eq!(deps.storage.get(PRIVATE_MEMO_KEY.as_bytes()), None);
+    }
+
+    #[test]
+    fn settling_twice_fails_with_already_settled() {
+        let new_option = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let mut expired = mock_env();
+        expired.block.height = 200_000;
+        let burned = |err| match err {
+            ContractError::AlreadySettled {
+                kind: SettlementKind::Burned,
+                height: 200_000,
+            } => {}
+            e => panic!("unexpected error: {}", e),
+        };
+
+        // burn after burn
+        let mut deps = new_option();
+        let _ = execute_burn(deps.as_mut(), expired.clone(), mock_info("anyone", &[])).unwrap();
+        let mut next = expired.clone();
+        next.block.height += 1;
+        burned(execute_burn(deps.as_mut(), next, mock_info("anyone", &[])).unwrap_err());
+
+        // exercise after burn
+        let info = mock_info("creator", &coins(40, "ETH"));
+        burned(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+        let msg = ExecuteMsg::ExerciseWithCallback {
+            callback_contract: "callback".to_string(),
+            callback_msg: Binary::default(),
+        };
+        let info = mock_info("creator", &coins(40, "ETH"));
+        burned(super::execute(deps.as_mut(), mock_env(), info, msg).unwrap_err());
+
+        // renounce after burn
+        let info = mock_info("creator", &[]);
+        burned(execute_renounce(deps.as_mut(), mock_env(), info).unwrap_err());
+
+        // burn after exercise
+        let mut deps = new_option();
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let _ = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        let err = execute_burn(deps.as_mut(), expired, mock_info("anyone", &[])).unwrap_err();
+        match err {
+            ContractError::AlreadySettled {
+                kind: SettlementKind::Executed,
+                height,
+            } => assert_eq!(height, mock_env().block.height),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // renounce after a renounce that closed the option
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            renounce_returns_to_creator: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info("creator", &[]);
+        let _ = execute_transfer(deps.as_mut(), mock_env(), info, "owner".to_string()).unwrap();
+        let _ = execute_renounce(deps.as_mut(), mock_env(), mock_info("owner", &[])).unwrap();
+        let err = execute_renounce(deps.as_mut(), mock_env(), mock_info("owner", &[])).unwrap_err();
+        match err {
+            ContractError::AlreadySettled {
+                kind: SettlementKind::Renounced,
+                height,
+            } => assert_eq!(height, mock_env().block.height),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn settlement_reports_coin_counts() {
+        let new_option = |collateral: &[Coin], counter_offer: Vec<Coin>| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer,
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", collateral);
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let counts = |res: &Response, collateral: &str, counter_offer: &str| {
+            assert!(res
+                .attributes
+                .contains(&attr("collateral_coin_count", collateral)));
+            assert!(res
+                .attributes
+                .contains(&attr("counter_offer_coin_count", counter_offer)));
+        };
+
+        let counter_offer = vec![coin(40, "ETH"), coin(1, "ATOM"), coin(2, "OSMO")];
+        let mut deps = new_option(&[coin(1, "BTC"), coin(2, "ATOM")], counter_offer.clone());
+        let info = mock_info("creator", &counter_offer);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        counts(&res, "2", "3");
+
+        let mut deps = new_option(&coins(1, "BTC"), coins(40, "ETH"));
+        let msg = ExecuteMsg::ExerciseWithCallback {
+            callback_contract: "callback".to_string(),
+            callback_msg: Binary::default(),
+        };
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let res = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        counts(&res, "1", "1");
+
+        let mut deps = new_option(&[], vec![]);
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute_burn(deps.as_mut(), env, mock_info("anyone", &[])).unwrap();
+        counts(&res, "0", "0");
+    }
+
+    #[test]
+    fn payment_tolerance() {
+        let new_option = |payment_tolerance_bps| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: vec![coin(10_000, "ETH"), coin(40, "ATOM")],
+                expires: 100_000,
+                payment_tolerance_bps,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).map(|_| deps)
+        };
+        let mismatch = |err| assert!(matches!(err, ContractError::CounterOfferMismatch { .. }));
+
+        // exact by default
+        let mut deps = new_option(0).unwrap();
+        let info = mock_info("creator", &[coin(9_999, "ETH"), coin(40, "ATOM")]);
+        mismatch(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+
+        // 1 bps of 10000 is one unit
+        let mut deps = new_option(1).unwrap();
+        let info = mock_info("creator", &[coin(9_998, "ETH"), coin(40, "ATOM")]);
+        mismatch(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+        // and nothing of 40
+        let info = mock_info("creator", &[coin(9_999, "ETH"), coin(39, "ATOM")]);
+        mismatch(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+        // overpaying or leaving out a coin is no more accepted than before
+        let info = mock_info("creator", &[coin(10_001, "ETH"), coin(40, "ATOM")]);
+        mismatch(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+        let info = mock_info("creator", &[coin(9_999, "ETH")]);
+        mismatch(execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err());
+
+        let paid = vec![coin(9_999, "ETH"), coin(40, "ATOM")];
+        let info = mock_info("creator", &paid);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: paid,
+            })
+        );
+        assert!(res.attributes.contains(&attr("payment_shortfall", "1ETH")));
+
+        // exact payments report no shortfall
+        let mut deps = new_option(1).unwrap();
+        let info = mock_info("creator", &[coin(10_000, "ETH"), coin(40, "ATOM")]);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "payment_shortfall"));
+
+        let err = new_option(11).err().unwrap();
+        assert!(matches!(
+            err,
+            ContractError::PaymentToleranceTooLarge {
+                max_bps: 10,
+                given_bps: 11
+            }
+        ));
+    }
+
+    #[test]
+    fn denoms() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(1, "ATOM"), coin(2, "ETH")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Denoms {}).unwrap();
+        let denoms: DenomsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            denoms,
+            DenomsResponse {
+                collateral_denoms: vec!["ATOM".to_string(), "BTC".to_string()],
+                counter_offer_denoms: vec!["ATOM".to_string(), "ETH".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn renounce() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            skip_self_sends: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // nothing to renounce while the creator owns it
+        let err = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::Renounce {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // only the owner can renounce
+        for sender in ["creator", "anyone"] {
+            let info = mock_info(sender, &[]);
+            let err = super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {})
+                .unwrap_err();
+            assert!(matches!(err, ContractError::Unauthorized {}));
+        }
+
+        let info = mock_info("owner", &[]);
+        let res = super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {}).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "renounce"),
+                attr("owner", "creator"),
+                attr("seq", "3")
+            ]
+        );
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().owner,
+            Addr::unchecked("creator")
+        );
+
+        // the creator can unlock the collateral right away, before expiry
+        let info = mock_info("creator", &[]);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".into(),
+                amount: coins(1, "BTC"),
+            })
+        );
+    }
+
+    #[test]
+    fn instantiate_with_owner() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owner: Some("client".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("broker", &coins(1, "BTC"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "transfer");
+        assert_eq!(res.events[0].attributes, vec![attr("owner", "client")]);
+
+        // the option is the client's from the start
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!(state.creator, Addr::unchecked("broker"));
+        assert_eq!(state.owner, Addr::unchecked("client"));
+        assert_eq!(state.seq, 1);
+        let msg = ExecuteMsg::Transfer {
+            recipient: "broker".to_string(),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("broker", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // an owner equal to the creator is the same as none
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owner: Some("creator".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events.len(), 0);
+    }
+
+    #[test]
+    fn redundant_transfer_is_rejected() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let transfer_to = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+        };
+
+        // to oneself
+        let info = mock_info("creator", &[]);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), info, transfer_to("creator")).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyOwner {}));
+
+        // twice to the same recipient, which owns it after the first time
+        let info = mock_info("creator", &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), info, transfer_to("alice")).unwrap();
+        let seq = query_config(deps.as_ref()).unwrap().seq;
+        let info = mock_info("alice", &[]);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), info, transfer_to("alice")).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyOwner {}));
+        assert_eq!(query_config(deps.as_ref()).unwrap().seq, seq);
+    }
+
+    #[test]
+    fn payment_assessment() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(10_000, "ETH"), coin(40, "ATOM")],
+            expires: 100_000,
+            payment_tolerance_bps: 1,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let assess = |funds: Vec<Coin>| {
+            let msg = QueryMsg::PaymentAssessment { funds };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<PaymentAssessmentResponse>(&res).unwrap()
+        };
+
+        assert_eq!(
+            assess(vec![coin(10_000, "ETH"), coin(40, "ATOM")]),
+            PaymentAssessmentResponse {
+                status: PaymentStatus::Exact {},
+                accepted: true,
+            }
+        );
+        assert_eq!(
+            assess(vec![coin(10_001, "ETH"), coin(40, "ATOM"), coin(1, "BTC")]),
+            PaymentAssessmentResponse {
+                status: PaymentStatus::Over {
+                    surplus: vec![coin(1, "BTC"), coin(1, "ETH")],
+                },
+                accepted: false,
+            }
+        );
+        // within the tolerance
+        assert_eq!(
+            assess(vec![coin(9_999, "ETH"), coin(40, "ATOM")]),
+            PaymentAssessmentResponse {
+                status: PaymentStatus::Under {
+                    shortfall: coins(1, "ETH"),
+                },
+                accepted: true,
+            }
+        );
+        // missing a coin entirely, while overpaying another
+        assert_eq!(
+            assess(vec![coin(10_001, "ETH")]),
+            PaymentAssessmentResponse {
+                status: PaymentStatus::Under {
+                    shortfall: coins(40, "ATOM"),
+                },
+                accepted: false,
+            }
+        );
+    }
+
+    #[test]
+    fn noop_is_rejected() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let err = super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Noop {}).unwrap_err();
+        assert!(matches!(err, ContractError::UnsupportedAction {}));
+        assert_eq!(err.error_code(), 21);
+    }
+
+    #[test]
+    fn expiry_reminder() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            reminder_target: Some("reminders".to_string()),
+            reminder_window_blocks: 100,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut trigger = |height| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let info = mock_info("keeper", &[]);
+            super::execute(
+                deps.as_mut(),
+                env,
+                info,
+                ExecuteMsg::TriggerExpiryReminder {},
+            )
+        };
+
+        // not within the window yet
+        let err = trigger(99_899).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::ReminderNotDue {
+                window_opens: 99_900
+            }
+        ));
+
+        // fires once
+        let res = trigger(99_900).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reminders".to_string(),
+                msg: to_binary(&ReminderExecuteMsg::ExpiryReminder { expires: 100_000 }).unwrap(),
+                funds: vec![],
+            })
+        );
+        let err = trigger(99_950).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyReminded {}));
+
+        // too late once expired
+        let err = trigger(100_000).unwrap_err();
+        assert!(matches!(err, ContractError::OptionExpired { .. }));
+
+        // a rolled option can be reminded of its new expiry
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let msg = ExecuteMsg::Roll {
+            new_counter_offer: coins(40, "ETH"),
+            new_expires: 200_000,
+        };
+        let _ = super::execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 199_950;
+        let info = mock_info("keeper", &[]);
+        let res = super::execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::TriggerExpiryReminder {},
+        );
+        assert_eq!(res.unwrap().messages.len(), 1);
+
+        // nothing to remind without a target
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            reminder_window_blocks: 100,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 99_950;
+        let info = mock_info("keeper", &[]);
+        let err = super::execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::TriggerExpiryReminder {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoReminderTarget {}));
+    }
+
+    #[test]
+    fn exercise_tip() {
+        let new_option = |exercise_tip: Coin| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                exercise_tip: Some(exercise_tip),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+            instantiate(deps.as_mut(), mock_env(), info, msg).map(|_| deps)
+        };
+        let send = |to: &str, amount: Vec<Coin>| {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: to.to_string(),
+                amount,
+            })
+        };
+
+        // the tip must be part of the funds
+        let err = new_option(coin(6, "ATOM")).err().unwrap();
+        assert!(matches!(err, ContractError::InsufficientCollateral {}));
+
+        // the tip is not collateral and cannot be reclaimed
+        let mut deps = new_option(coin(5, "ATOM")).unwrap();
+        let state = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(state.collateral, coins(1, "BTC"));
+        assert_eq!(state.exercise_tip, Some(coin(5, "ATOM")));
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, vec![coin(1, "BTC"), coin(5, "ATOM")]);
+        let msg = ExecuteMsg::ReclaimExcessFunds {
+            denom: "ATOM".to_string(),
+        };
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToReclaim { .. }));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Reconcile {}).unwrap();
+        let reconcile: ReconcileResponse = from_binary(&res).unwrap();
+        assert_eq!(reconcile.discrepancies, vec![]);
+
+        // paid to the exerciser
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let res = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+            vec![
+                send("creator", coins(40, "ETH")),
+                send("owner", coins(1, "BTC")),
+                send("owner", coins(5, "ATOM")),
+            ]
+        );
+
+        // returned to the creator on burn
+        let mut deps = new_option(coin(5, "ATOM")).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let info = mock_info("creator", &[]);
+        let res = super::execute(deps.as_mut(), env, info, ExecuteMsg::Burn {}).unwrap();
+        assert_eq!(
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+            vec![
+                send("creator", coins(1, "BTC")),
+                send("creator", coins(5, "ATOM")),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocklist() {
+        let new_option = || {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(1, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps.querier.update_wasm(|query| match query {
+                WasmQuery::ContractInfo { .. } => {
+                    let mut info = ContractInfoResponse::new(1, "creator");
+                    info.admin = Some("admin".to_string());
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+                }
+                _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "wasm".to_string(),
+                }),
+            });
+            deps
+        };
+        let update = |add: &[&str], remove: &[&str]| ExecuteMsg::UpdateBlocklist {
+            add: add.iter().map(|a| a.to_string()).collect(),
+            remove: remove.iter().map(|a| a.to_string()).collect(),
+        };
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+        };
+
+        // only the contract admin manages the blocklist
+        let mut deps = new_option();
+        let msg = update(&["sanctioned"], &[]);
+        let err = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // no transfers to blocked addresses
+        let info = mock_info("creator", &[]);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), info, transfer("sanctioned")).unwrap_err();
+        assert!(matches!(err, ContractError::BlockedAddress {}));
+        let info = mock_info("creator", &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), info, transfer("owner")).unwrap();
+
+        // no payouts to them either, until they are removed
+        let msg = update(&["owner"], &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let err = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::BlockedAddress {}));
+        let msg = update(&[], &["owner"]);
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        assert_eq!(
+            BLOCKLIST.load(&deps.storage).unwrap(),
+            vec![Addr::unchecked("sanctioned")]
+        );
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let _ = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        )
+        .unwrap();
+
+        // a blocked creator cannot get the collateral back
+        let mut deps = new_option();
+        let msg = update(&["creator"], &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let err = super::execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::Burn {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::BlockedAddress {}));
+    }
+
+    #[test]
+    fn expected_collateral() {
+        let instantiate_with = |funds: &[Coin]| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                expected_collateral: Some(vec![coin(5, "ATOM"), coin(1, "BTC")]),
+                ..Default::default()
+            };
+            let info = mock_info("creator", funds);
+            instantiate(deps.as_mut(), mock_env(), info, msg)
+        };
+
+        // the same coins in another order, split up or with empty ones are fine
+        for funds in [
+            vec![coin(1, "BTC"), coin(5, "ATOM")],
+            vec![coin(2, "ATOM"), coin(1, "BTC"), coin(3, "ATOM")],
+            vec![coin(1, "BTC"), coin(0, "ETH"), coin(5, "ATOM")],
+        ] {
+            instantiate_with(&funds).unwrap();
+        }
+
+        // missing, different or additional coins are not
+        for funds in [
+            vec![coin(1, "BTC")],
+            vec![coin(1, "BTC"), coin(4, "ATOM")],
+            vec![coin(1, "BTC"), coin(5, "ATOM"), coin(1, "ETH")],
+        ] {
+            let err = instantiate_with(&funds).unwrap_err();
+            match err {
+                ContractError::CollateralMismatch { expected, got } => {
+                    assert_eq!(expected, vec![coin(5, "ATOM"), coin(1, "BTC")]);
+                    assert_eq!(got, sum_coins(funds.iter()));
+                }
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn preview_exercise_messages() {
+        let configs = [
+            InstantiateMsg::default(),
+            InstantiateMsg {
+                settlement_router: Some("router".to_string()),
+                ..Default::default()
+            },
+            InstantiateMsg {
+                exercise_tip: Some(coin(5, "ATOM")),
+                ..Default::default()
+            },
+            InstantiateMsg {
+                skip_self_sends: true,
+                ..Default::default()
+            },
+            InstantiateMsg {
+                funding_allowlist: Some(vec!["treasury".to_string()]),
+                ..Default::default()
+            },
+        ];
+        let payments = [
+            ("creator", coins(40, "ETH")),
+            ("creator", vec![]),
+            ("treasury", coins(40, "ETH")),
+            ("creator", coins(39, "ETH")),
+        ];
+        for config in configs {
+            for (sender, funds) in &payments {
+                let mut deps = mock_dependencies();
+                let msg = InstantiateMsg {
+                    counter_offer: coins(40, "ETH"),
+                    expires: 100_000,
+                    ..config.clone()
+                };
+                let info = mock_info("creator", &[coin(1, "BTC"), coin(5, "ATOM")]);
+                let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+                let msg = QueryMsg::PreviewExerciseMessages {
+                    sender: sender.to_string(),
+                    funds: funds.clone(),
+                };
+                let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+                let preview: PreviewExerciseMessagesResponse = from_binary(&res).unwrap();
+
+                let info = mock_info(sender, funds);
+                match super::execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    info,
+                    ExecuteMsg::Execute {
+                        deadline_height: None,
+                    },
+                ) {
+                    Ok(res) => {
+                        let messages: Vec<_> = res.messages.into_iter().map(|m| m.msg).collect();
+                        assert!(!messages.is_empty());
+                        assert_eq!(preview.messages, messages);
+                        assert_eq!(preview.error, None);
+                    }
+                    Err(err) => {
+                        assert_eq!(preview.messages, vec![]);
+                        assert_eq!(preview.error, Some(err.to_string()));
+                        assert_eq!(preview.error_code, Some(err.error_code()));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ownership_stats() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let stats = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+            let res = query(deps.as_ref(), mock_env(), QueryMsg::OwnershipStats {}).unwrap();
+            from_binary::<OwnershipStatsResponse>(&res).unwrap()
+        };
+        let at = |blocks_later: u64| {
+            let mut env = mock_env();
+            env.block.height += blocks_later;
+            env
+        };
+        assert_eq!(
+            stats(&deps),
+            OwnershipStatsResponse {
+                total_transfers: 0,
+                avg_hold_blocks: 0,
+            }
+        );
+
+        // the creator holds for 100 blocks, alice for 300, bob for 100
+        for (sender, recipient, height) in [("creator", "alice", 100), ("alice", "bob", 400)] {
+            let msg = ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+            };
+            let _ = super::execute(deps.as_mut(), at(height), mock_info(sender, &[]), msg).unwrap();
+        }
+        assert_eq!(
+            stats(&deps),
+            OwnershipStatsResponse {
+                total_transfers: 2,
+                avg_hold_blocks: 200,
+            }
+        );
+        let info = mock_info("bob", &[]);
+        let _ = super::execute(deps.as_mut(), at(500), info, ExecuteMsg::Renounce {}).unwrap();
+        let expected = OwnershipStatsResponse {
+            total_transfers: 3,
+            avg_hold_blocks: 166,
+        };
+        assert_eq!(stats(&deps), expected);
+
+        // still available once the option is settled
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let _ = super::execute(
+            deps.as_mut(),
+            at(600),
+            info,
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(stats(&deps), expected);
+    }
+
+    #[test]
+    fn creator_shares() {
+        let new_option = |shares: &[(&str, u16)]| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(41, "ETH"),
+                expires: 100_000,
+                creator_shares: Some(
+                    shares
+                        .iter()
+                        .map(|(address, bps)| CreatorShare {
+                            address: address.to_string(),
+                            bps: *bps,
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(3, "BTC"));
+            instantiate(deps.as_mut(), mock_env(), info, msg).map(|_| deps)
+        };
+        let send = |to: &str, amount: Vec<Coin>| {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: to.to_string(),
+                amount,
             })
+        };
+
+        // shares must add up exactly, without empty ones
+        for shares in [
+            vec![],
+            vec![("alice", 6000), ("bob", 3999)],
+            vec![("alice", 6000), ("bob", 4001)],
+            vec![("alice", 10_000), ("bob", 0)],
+        ] {
+            let err = new_option(&shares).err().unwrap();
+            assert!(matches!(err, ContractError::InvalidShares {}));
+        }
+
+        // the counter_offer is split on exercise, rounding in favour of the last share
+        let mut deps = new_option(&[("alice", 6000), ("bob", 4000)]).unwrap();
+        let info = mock_info("creator", &coins(41, "ETH"));
+        let res = super::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Execute {
+                deadline_height: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+            vec![
+                send("alice", coins(24, "ETH")),
+                send("bob", coins(17, "ETH")),
+                send("creator", coins(3, "BTC")),
+            ]
         );
+
+        // and so is the collateral on burn
+        let mut deps = new_option(&[("alice", 6000), ("bob", 4000)]).unwrap();
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let res = super::execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            ExecuteMsg::Burn {},
+        )
+        .unwrap();
         assert_eq!(
-            res.messages[1].msg,
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+            vec![send("alice", coins(1, "BTC")), send("bob", coins(2, "BTC"))]
+        );
+    }
+
+    #[test]
+    fn zero_amount_coins_do_not_affect_payment() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // an empty coin does not make up for a missing one
+        let info = mock_info("creator", &[coin(40, "ETH"), coin(0, "ATOM")]);
+        let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+        assert!(matches!(err, ContractError::CounterOfferMismatch { .. }));
+
+        // and is ignored next to the right payment, in any order
+        let info = mock_info(
+            "creator",
+            &[coin(5, "ATOM"), coin(0, "BTC"), coin(40, "ETH")],
+        );
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
             CosmosMsg::Bank(BankMsg::Send {
-                to_address: "owner".into(),
-                amount: collateral,
+                to_address: "creator".to_string(),
+                amount: vec![coin(5, "ATOM"), coin(40, "ETH")],
             })
         );
+    }
 
-        // check deleted
-        let _ = query_config(deps.as_ref()).unwrap_err();
+    #[test]
+    fn storage_layout() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StorageLayout {}).unwrap();
+        let layout: StorageLayoutResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            layout,
+            StorageLayoutResponse {
+                config_key: CONFIG_KEY.to_string(),
+                options_namespace: None,
+                counter_key: None,
+            }
+        );
+
+        // the option can be read from the raw key
+        let raw = deps.storage.get(layout.config_key.as_bytes()).unwrap();
+        let state: State = cosmwasm_std::from_slice(&raw).unwrap();
+        assert_eq!(state, CONFIG.load(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn renounce_returns_to_creator() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            exercise_tip: Some(coin(2, "ATOM")),
+            renounce_returns_to_creator: true,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[coin(1, "BTC"), coin(2, "ATOM")]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // still only the owner can renounce
+        let info = mock_info("creator", &[]);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // before expiry, the collateral and tip go straight back to the creator
+        let info = mock_info("owner", &[]);
+        let res = super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {}).unwrap();
+        let sends: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            sends,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(1, "BTC"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(2, "ATOM"),
+                }),
+            ]
+        );
+        assert_eq!(res.attributes[0], attr("action", "renounce"));
+        let data: State = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(data.owner, Addr::unchecked("owner"));
+
+        // and the option is closed
+        assert_eq!(
+            query_status_code(deps.as_ref(), mock_env()).unwrap(),
+            STATUS_CANCELLED
+        );
+        let err = execute_execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &coins(40, "ETH")),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::AlreadySettled {
+                kind: SettlementKind::Renounced,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn digest() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let digest = |deps: Deps| -> Binary {
+            let res = query(deps, mock_env(), QueryMsg::Digest {}).unwrap();
+            from_binary::<DigestResponse>(&res).unwrap().digest
+        };
+        let terms = br#"{"creator":"creator","collateral":[{"denom":"BTC","amount":"1"}],"counter_offer":[{"denom":"ATOM","amount":"5"},{"denom":"ETH","amount":"40"}],"expires":100000}"#;
+        let first = digest(deps.as_ref());
+        assert_eq!(first.as_slice(), Sha256::digest(terms).as_slice());
+        assert_eq!(digest(deps.as_ref()), first);
+
+        // the owner is not part of the terms
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(digest(deps.as_ref()), first);
+
+        // new terms, new digest
+        let mut env = mock_env();
+        env.block.height = 100_000;
+        let msg = ExecuteMsg::Roll {
+            new_counter_offer: vec![coin(40, "ETH"), coin(5, "ATOM")],
+            new_expires: 150_000,
+        };
+        let _ = super::execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        assert_ne!(digest(deps.as_ref()), first);
+    }
+
+    #[test]
+    fn exercise_deadline() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let height = mock_env().block.height;
+
+        // included after the deadline
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let msg = ExecuteMsg::Execute {
+            deadline_height: Some(height - 1),
+        };
+        let err = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::ExerciseDeadlinePassed {}));
+
+        // the deadline block itself is still fine
+        let info = mock_info("creator", &coins(40, "ETH"));
+        let msg = ExecuteMsg::Execute {
+            deadline_height: Some(height),
+        };
+        let res = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "execute"));
+    }
+
+    #[test]
+    fn amend() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let amend = |counter_offer: Vec<Coin>, expires| ExecuteMsg::Amend {
+            counter_offer,
+            expires,
+        };
+
+        // only the creator, and only with a valid expiry
+        let msg = amend(coins(50, "ETH"), 200_000);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let msg = amend(coins(50, "ETH"), mock_env().block.height);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::OptionExpired { .. }));
+
+        let msg = amend(coins(50, "ETH"), 200_000);
+        let res =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "amend"));
+        let state = query_config(deps.as_ref()).unwrap();
+        assert_eq!(state.counter_offer, coins(50, "ETH"));
+        assert_eq!(state.expires, 200_000);
+
+        // fixed once the option changed hands
+        let msg = ExecuteMsg::Transfer {
+            recipient: "owner".to_string(),
+        };
+        let _ = super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = amend(coins(1, "ETH"), 200_000);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyTransferred {}));
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().counter_offer,
+            coins(50, "ETH")
+        );
+
+        // and stay fixed after the owner renounced it back to the creator
+        let info = mock_info("owner", &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Renounce {}).unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().owner, "creator");
+        let msg = amend(coins(1, "ETH"), 200_000);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyTransferred {}));
+
+        // nothing to amend once settled
+        let info = mock_info("creator", &coins(50, "ETH"));
+        let _ = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        let msg = amend(coins(1, "ETH"), 200_000);
+        let err =
+            super::execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadySettled { .. }));
+    }
+
+    #[test]
+    fn amend_validates_counter_offer() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        for counter_offer in [
+            vec![],
+            vec![coin(0, "ETH")],
+            vec![coin(40, "ETH"), coin(0, "ATOM")],
+            vec![coin(40, "ETH"), coin(5, "ATOM"), coin(2, "ETH")],
+        ] {
+            let msg = ExecuteMsg::Amend {
+                counter_offer,
+                expires: 100_000,
+            };
+            let info = mock_info("creator", &[]);
+            let err = super::execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidCounterOffer {}));
+        }
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().counter_offer,
+            coins(40, "ETH")
+        );
+    }
+
+    #[test]
+    fn counter_offer_history() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let history = |deps: Deps, limit| -> Vec<CounterOfferChange> {
+            let msg = QueryMsg::CounterOfferHistory { limit };
+            let res: CounterOfferHistoryResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.changes
+        };
+        assert_eq!(history(deps.as_ref(), None), vec![]);
+
+        let start = mock_env().block.height;
+        for i in 1..=MAX_COUNTER_OFFER_HISTORY as u64 + 5 {
+            let mut env = mock_env();
+            env.block.height = start + i;
+            let msg = ExecuteMsg::Amend {
+                counter_offer: coins(40 + i as u128, "ETH"),
+                expires: 100_000,
+            };
+            let _ = super::execute(deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        }
+
+        // the oldest changes are dropped
+        let changes = history(deps.as_ref(), None);
+        assert_eq!(changes.len(), MAX_COUNTER_OFFER_HISTORY);
+        assert_eq!(
+            changes[0],
+            CounterOfferChange {
+                counter_offer: coins(46, "ETH"),
+                height: start + 6,
+            }
+        );
+        assert!(changes.windows(2).all(|w| w[0].height < w[1].height));
+
+        // the latest ones, still in order
+        assert_eq!(
+            history(deps.as_ref(), Some(2)),
+            changes[MAX_COUNTER_OFFER_HISTORY - 2..].to_vec()
+        );
+        assert_eq!(history(deps.as_ref(), Some(100)), changes);
+    }
+
+    #[test]
+    fn free_exercise() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: vec![],
+            expires: 100_000,
+            owner: Some("owner".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // nothing to pay means nothing may be sent
+        let info = mock_info("owner", &coins(40, "ETH"));
+        let err = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap_err();
+        assert!(matches!(err, ContractError::CounterOfferMismatch { .. }));
+
+        // only the collateral moves
+        let info = mock_info("owner", &[]);
+        let res = execute_execute(deps.as_mut(), mock_env(), info, None).unwrap();
+        let sends: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+        assert_eq!(
+            sends,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: coins(1, "BTC"),
+            })]
+        );
+    }
+
+    #[test]
+    fn roll_pays_expiry_split() {
+        let new_option = |owner: &str| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                counter_offer: coins(40, "ETH"),
+                expires: 100_000,
+                owner: Some(owner.to_string()),
+                expiry_split_bps: Some(1000),
+                ..Default::default()
+            };
+            let info = mock_info("creator", &coins(101, "BTC"));
+            let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 200_000;
+
+        // the owner gets their share of the expired option, as on burn
+        let mut deps = new_option("owner");
+        let info = mock_info("creator", &[]);
+        let res =
+            execute_roll(deps.as_mut(), env.clone(), info, coins(50, "ETH"), 300_000).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: coins(10, "BTC"),
+            })
+        );
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().collateral,
+            coins(91, "BTC")
+        );
+
+        // nothing to pay while the creator holds it
+        let mut deps = new_option("creator");
+        let info = mock_info("creator", &[]);
+        let res = execute_roll(deps.as_mut(), env, info, coins(50, "ETH"), 300_000).unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(
+            query_config(deps.as_ref()).unwrap().collateral,
+            coins(101, "BTC")
+        );
+    }
+
+    #[test]
+    fn roll_ends_the_holding() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            owner: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let roll = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, height| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let info = mock_info("creator", &[]);
+            execute_roll(deps.as_mut(), env, info, coins(40, "ETH"), height + 100_000).unwrap();
+            query_ownership_stats(deps.as_ref()).unwrap()
+        };
+        let held = 100_000 - mock_env().block.height;
+
+        // alice held the option until it was rolled back to the creator
+        let expected = OwnershipStatsResponse {
+            total_transfers: 1,
+            avg_hold_blocks: held,
+        };
+        assert_eq!(roll(&mut deps, 100_000), expected);
+        // the creator keeping it is no change of hands
+        assert_eq!(roll(&mut deps, 200_000), expected);
+    }
+
+    #[test]
+    fn admin_log() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            counter_offer: coins(40, "ETH"),
+            expires: 100_000,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &coins(1, "BTC"));
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { .. } => {
+                let mut info = ContractInfoResponse::new(1, "creator");
+                info.admin = Some("admin".to_string());
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+        let admin_log = |deps: Deps, start_after, limit| -> Vec<AdminLogEntry> {
+            let msg = QueryMsg::AdminLog { start_after, limit };
+            let res: AdminLogResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.entries
+        };
+        assert_eq!(admin_log(deps.as_ref(), None, None), vec![]);
+
+        let msgs = [
+            ExecuteMsg::UpdateCodeChecksum {
+                checksum: Binary::from(vec![0xab; 32]),
+            },
+            ExecuteMsg::UpdateBlocklist {
+                add: vec!["sanctioned".to_string()],
+                remove: vec![],
+            },
+            ExecuteMsg::UpdateBlocklist {
+                add: vec![],
+                remove: vec!["sanctioned".to_string()],
+            },
+        ];
+        for (i, msg) in msgs.iter().enumerate() {
+            let mut env = mock_env();
+            env.block.height += i as u64;
+            let info = mock_info("admin", &[]);
+            let _ = super::execute(deps.as_mut(), env, info, msg.clone()).unwrap();
+        }
+        // rejected actions are not logged
+        let info = mock_info("creator", &[]);
+        let _ = super::execute(deps.as_mut(), mock_env(), info, msgs[1].clone()).unwrap_err();
+
+        let entries = admin_log(deps.as_ref(), None, None);
+        assert_eq!(
+            entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        let kinds: Vec<_> = entries.iter().map(|e| e.action.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "update_code_checksum",
+                "update_blocklist",
+                "update_blocklist"
+            ]
+        );
+        for (i, (entry, msg)) in entries.iter().zip(&msgs).enumerate() {
+            assert_eq!(entry.action.admin, Addr::unchecked("admin"));
+            assert_eq!(entry.action.height, mock_env().block.height + i as u64);
+            let payload: ExecuteMsg = from_slice(entry.action.payload_json.as_bytes()).unwrap();
+            assert_eq!(&payload, msg);
+        }
+        assert_eq!(
+            entries[1].action.payload_json,
+            r#"{"update_blocklist":{"add":["sanctioned"],"remove":[]}}"#
+        );
+
+        // paged by id
+        assert_eq!(
+            admin_log(deps.as_ref(), None, Some(2)),
+            entries[..2].to_vec()
+        );
+        assert_eq!(
+            admin_log(deps.as_ref(), Some(2), None),
+            entries[2..].to_vec()
+        );
+        assert_eq!(admin_log(deps.as_ref(), Some(3), None), vec![]);
     }
 }
@@ -1,6 +1,8 @@
 use cosmwasm_std::{Coin, StdError};
 use thiserror::Error;
 
+use crate::state::SettlementKind;
+
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
@@ -21,6 +23,225 @@ pub enum ContractError {
         counter_offer: Vec<Coin>,
     },
 
+    #[error("minimum hold period not met (available at {available_at:?})")]
+    MinHoldNotMet { available_at: u64 },
+
+    #[error("nothing to reclaim (denom {denom:?})")]
+    NothingToReclaim { denom: String },
+
+    #[error("callback failed: {reason}")]
+    CallbackFailed { reason: String },
+
     #[error("do not send funds with burn")]
     FundsSentWithBurn {},
-}
\ No newline at end of file
+
+    #[error("expiry too soon (min blocks ahead {min_blocks_ahead:?}, given {given_distance:?})")]
+    ExpiryTooSoon {
+        min_blocks_ahead: u64,
+        given_distance: u64,
+    },
+
+    #[error("collateral and counter_offer must each be a single coin of distinct denoms")]
+    RequiresSingleDenom {},
+
+    #[error("invalid metadata signature")]
+    InvalidMetadataSignature {},
+
+    #[error("insufficient collateral")]
+    InsufficientCollateral {},
+
+    #[error("expiry split too large (max bps {max_bps:?}, given {given_bps:?})")]
+    ExpirySplitTooLarge { max_bps: u64, given_bps: u64 },
+
+    #[error("funder not allowed")]
+    FunderNotAllowed {},
+
+    #[error("expiry too far, the option would never expire")]
+    ExpiryTooFar {},
+
+    #[error("option already settled ({kind:?} at height {height:?})")]
+    AlreadySettled { kind: SettlementKind, height: u64 },
+
+    #[error("basis points above 10000 (given {bps:?})")]
+    InvalidBps { bps: u16 },
+
+    #[error("payment tolerance too large (max bps {max_bps:?}, given {given_bps:?})")]
+    PaymentToleranceTooLarge { max_bps: u64, given_bps: u64 },
+
+    #[error("recipient already owns the option")]
+    AlreadyOwner {},
+
+    #[error("unsupported action")]
+    UnsupportedAction {},
+
+    #[error("no reminder target configured")]
+    NoReminderTarget {},
+
+    #[error("reminder not due yet (window opens {window_opens:?})")]
+    ReminderNotDue { window_opens: u64 },
+
+    #[error("expiry reminder already sent")]
+    AlreadyReminded {},
+
+    #[error("blocked address")]
+    BlockedAddress {},
+
+    #[error("collateral mismatch (expected {expected:?}, got {got:?})")]
+    CollateralMismatch { expected: Vec<Coin>, got: Vec<Coin> },
+
+    #[error("creator shares must be positive and sum up to 10000 bps")]
+    InvalidShares {},
+
+    #[error("exercise deadline passed")]
+    ExerciseDeadlinePassed {},
+
+    #[error("option already transferred by its creator")]
+    AlreadyTransferred {},
+
+    #[error("counter_offer must not be empty, contain zero amounts or repeat a denom")]
+    InvalidCounterOffer {},
+
+    #[error("gift must contain at least one coin and no zero amounts")]
+    EmptyGift {},
+
+    #[error("gift cannot take all of the collateral")]
+    GiftEmptiesCollateral {},
+}
+
+impl ContractError {
+    /// Stable numeric code for clients that match on errors. Never reuse or change a code.
+    pub fn error_code(&self) -> u32 {
+        match self {
+            ContractError::Std(_) => 1,
+            ContractError::OptionExpired { .. } => 2,
+            ContractError::OptionNotExpired { .. } => 3,
+            ContractError::Unauthorized {} => 4,
+            ContractError::CounterOfferMismatch { .. } => 5,
+            ContractError::FundsSentWithBurn {} => 6,
+            ContractError::MinHoldNotMet { .. } => 7,
+            ContractError::NothingToReclaim { .. } => 8,
+            ContractError::CallbackFailed { .. } => 9,
+            ContractError::ExpiryTooSoon { .. } => 10,
+            ContractError::RequiresSingleDenom {} => 11,
+            ContractError::InvalidMetadataSignature {} => 12,
+            ContractError::InsufficientCollateral {} => 13,
+            ContractError::ExpirySplitTooLarge { .. } => 14,
+            ContractError::FunderNotAllowed {} => 15,
+            ContractError::ExpiryTooFar {} => 16,
+            ContractError::AlreadySettled { .. } => 17,
+            ContractError::InvalidBps { .. } => 18,
+            ContractError::PaymentToleranceTooLarge { .. } => 19,
+            ContractError::AlreadyOwner {} => 20,
+            ContractError::UnsupportedAction {} => 21,
+            ContractError::NoReminderTarget {} => 22,
+            ContractError::ReminderNotDue { .. } => 23,
+            ContractError::AlreadyReminded {} => 24,
+            ContractError::BlockedAddress {} => 25,
+            ContractError::CollateralMismatch { .. } => 26,
+            ContractError::InvalidShares {} => 27,
+            ContractError::ExerciseDeadlinePassed {} => 28,
+            ContractError::AlreadyTransferred {} => 29,
+            ContractError::InvalidCounterOffer {} => 30,
+            ContractError::EmptyGift {} => 31,
+            ContractError::GiftEmptiesCollateral {} => 32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::coins;
+    use std::collections::HashSet;
+
+    // Lists one instance of every variant, next to the pattern it matches. The patterns also form
+    // a match over ContractError, so a variant missing here fails to compile.
+    macro_rules! all_variants {
+        ($($pattern:pat => $instance:expr,)*) => {
+            fn all_variants() -> Vec<ContractError> {
+                fn _exhaustive(err: &ContractError) {
+                    match err {
+                        $($pattern => {})*
+                    }
+                }
+                vec![$({
+                    let err = $instance;
+                    assert!(matches!(err, $pattern), "{:?} does not match its pattern", err);
+                    err
+                }),*]
+            }
+        };
+    }
+
+    all_variants! {
+        ContractError::Std(_) => ContractError::Std(StdError::generic_err("error")),
+        ContractError::OptionExpired { .. } => ContractError::OptionExpired { expired: 1 },
+        ContractError::OptionNotExpired { .. } => ContractError::OptionNotExpired { expires: 1 },
+        ContractError::Unauthorized {} => ContractError::Unauthorized {},
+        ContractError::CounterOfferMismatch { .. } => ContractError::CounterOfferMismatch {
+            offer: coins(1, "BTC"),
+            counter_offer: coins(2, "BTC"),
+        },
+        ContractError::FundsSentWithBurn {} => ContractError::FundsSentWithBurn {},
+        ContractError::MinHoldNotMet { .. } => ContractError::MinHoldNotMet { available_at: 1 },
+        ContractError::NothingToReclaim { .. } => ContractError::NothingToReclaim {
+            denom: "BTC".to_string(),
+        },
+        ContractError::CallbackFailed { .. } => ContractError::CallbackFailed {
+            reason: "reason".to_string(),
+        },
+        ContractError::ExpiryTooSoon { .. } => ContractError::ExpiryTooSoon {
+            min_blocks_ahead: 2,
+            given_distance: 1,
+        },
+        ContractError::RequiresSingleDenom {} => ContractError::RequiresSingleDenom {},
+        ContractError::InvalidMetadataSignature {} => ContractError::InvalidMetadataSignature {},
+        ContractError::InsufficientCollateral {} => ContractError::InsufficientCollateral {},
+        ContractError::ExpirySplitTooLarge { .. } => ContractError::ExpirySplitTooLarge {
+            max_bps: 1,
+            given_bps: 2,
+        },
+        ContractError::FunderNotAllowed {} => ContractError::FunderNotAllowed {},
+        ContractError::ExpiryTooFar {} => ContractError::ExpiryTooFar {},
+        ContractError::AlreadySettled { .. } => ContractError::AlreadySettled {
+            kind: SettlementKind::Burned,
+            height: 1,
+        },
+        ContractError::InvalidBps { .. } => ContractError::InvalidBps { bps: 10_001 },
+        ContractError::PaymentToleranceTooLarge { .. } => ContractError::PaymentToleranceTooLarge {
+            max_bps: 1,
+            given_bps: 2,
+        },
+        ContractError::AlreadyOwner {} => ContractError::AlreadyOwner {},
+        ContractError::UnsupportedAction {} => ContractError::UnsupportedAction {},
+        ContractError::NoReminderTarget {} => ContractError::NoReminderTarget {},
+        ContractError::ReminderNotDue { .. } => ContractError::ReminderNotDue { window_opens: 1 },
+        ContractError::AlreadyReminded {} => ContractError::AlreadyReminded {},
+        ContractError::BlockedAddress {} => ContractError::BlockedAddress {},
+        ContractError::CollateralMismatch { .. } => ContractError::CollateralMismatch {
+            expected: coins(1, "BTC"),
+            got: coins(2, "BTC"),
+        },
+        ContractError::InvalidShares {} => ContractError::InvalidShares {},
+        ContractError::ExerciseDeadlinePassed {} => ContractError::ExerciseDeadlinePassed {},
+        ContractError::AlreadyTransferred {} => ContractError::AlreadyTransferred {},
+        ContractError::InvalidCounterOffer {} => ContractError::InvalidCounterOffer {},
+        ContractError::EmptyGift {} => ContractError::EmptyGift {},
+        ContractError::GiftEmptiesCollateral {} => ContractError::GiftEmptiesCollateral {},
+    }
+
+    #[test]
+    fn all_error_variants_have_display() {
+        for err in all_variants() {
+            let msg = err.to_string();
+            assert!(!msg.trim().is_empty(), "empty display for {:?}", err);
+        }
+    }
+
+    #[test]
+    fn all_error_variants_have_unique_codes() {
+        let variants = all_variants();
+        let codes: HashSet<u32> = variants.iter().map(ContractError::error_code).collect();
+        assert_eq!(codes.len(), variants.len());
+    }
+}
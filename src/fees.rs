@@ -0,0 +1,88 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{StdError, Uint128};
+
+use crate::error::ContractError;
+
+// one whole in basis points
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Share of `amount` given in basis points, rounded down.
+pub fn apply_bps(amount: Uint128, bps: u16) -> Result<Uint128, ContractError> {
+    if bps > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidBps { bps });
+    }
+    // widened so 'amount * bps' can never overflow
+    let share = amount
+        .full_mul(bps)
+        .checked_div(BPS_DENOMINATOR.into())
+        .map_err(StdError::from)?;
+    Ok(Uint128::try_from(share).map_err(StdError::from)?)
+}
+
+/// What is left of `amount` after taking its share in basis points, so it is rounded up.
+pub fn remainder_after_bps(amount: Uint128, bps: u16) -> Result<Uint128, ContractError> {
+    let share = apply_bps(amount, bps)?;
+    Ok(amount.checked_sub(share).map_err(StdError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_bps_rounds_down() {
+        assert_eq!(apply_bps(Uint128::new(15), 1000).unwrap(), Uint128::new(1));
+        assert_eq!(apply_bps(Uint128::new(9), 1000).unwrap(), Uint128::zero());
+        assert_eq!(apply_bps(Uint128::new(10_000), 1).unwrap(), Uint128::new(1));
+        assert_eq!(
+            remainder_after_bps(Uint128::new(15), 1000).unwrap(),
+            Uint128::new(14)
+        );
+        assert_eq!(
+            remainder_after_bps(Uint128::new(9), 1000).unwrap(),
+            Uint128::new(9)
+        );
+    }
+
+    #[test]
+    fn apply_bps_edges() {
+        // zero amount or bps
+        assert_eq!(apply_bps(Uint128::zero(), 5000).unwrap(), Uint128::zero());
+        assert_eq!(apply_bps(Uint128::new(100), 0).unwrap(), Uint128::zero());
+        assert_eq!(
+            remainder_after_bps(Uint128::new(100), 0).unwrap(),
+            Uint128::new(100)
+        );
+
+        // all of it
+        assert_eq!(
+            apply_bps(Uint128::new(100), 10_000).unwrap(),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            remainder_after_bps(Uint128::new(100), 10_000).unwrap(),
+            Uint128::zero()
+        );
+
+        // no overflow on the largest amounts
+        assert_eq!(apply_bps(Uint128::MAX, 10_000).unwrap(), Uint128::MAX);
+        assert_eq!(
+            apply_bps(Uint128::MAX, 5000).unwrap(),
+            Uint128::new(u128::MAX / 2)
+        );
+        assert_eq!(
+            apply_bps(Uint128::MAX, 5000).unwrap()
+                + remainder_after_bps(Uint128::MAX, 5000).unwrap(),
+            Uint128::MAX
+        );
+
+        // more than everything
+        for bps in [10_001, u16::MAX] {
+            let err = apply_bps(Uint128::new(100), bps).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidBps { bps: b } if b == bps));
+            let err = remainder_after_bps(Uint128::new(100), bps).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidBps { .. }));
+        }
+    }
+}
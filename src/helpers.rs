@@ -0,0 +1,34 @@
+use cosmwasm_std::Storage;
+
+/// Overwrites the value stored at `key` with zeros before removing it.
+///
+/// Defense in depth for chains where a deletion is not immediately final: a node that only
+/// applies the write, not the removal, is left with zeros instead of the old value. Past values
+/// stay in the chain history either way, so this does not make any data private.
+pub fn secure_delete(storage: &mut dyn Storage, key: &[u8]) {
+    if let Some(value) = storage.get(key) {
+        storage.set(key, &vec![0u8; value.len()]);
+    }
+    storage.remove(key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn secure_delete_removes_key() {
+        let mut storage = MockStorage::new();
+        storage.set(b"config", b"secret");
+        storage.set(b"other", b"kept");
+
+        secure_delete(&mut storage, b"config");
+        assert_eq!(storage.get(b"config"), None);
+        assert_eq!(storage.get(b"other"), Some(b"kept".to_vec()));
+
+        // missing keys are fine
+        secure_delete(&mut storage, b"missing");
+        assert_eq!(storage.get(b"missing"), None);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+mod fees;
+mod helpers;
 pub mod msg;
 pub mod state;
 
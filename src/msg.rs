@@ -1,15 +1,88 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::{Coin}; 
-use crate::state::State;
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Decimal, Uint128}; 
+use crate::state::{
+    AdminAction, ContractMeta, CounterOfferChange, CreationRecord, SignedMetadataRecord, State,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct InstantiateMsg {
     //The owner, creator, and collateral variables all come from MessageInfo. 
 
     //MessageInfo includes a "sender" variable and a "funds" variable. 'sender' is the address that initiated the action (i.e. the message). 'funds' are the funds that are sent to the contract as part of `MsgInstantiateContract`. The transfer is processed in bank before the contract is executed such that the new balance is visible during contract execution.
     pub counter_offer: Vec<Coin>, 
     pub expires: u64, 
+    /// Optional escrow/compliance contract that receives all settlement funds and
+    /// forwards them according to the `RouteSettlement` legs instead of direct bank sends
+    pub settlement_router: Option<String>,
+    /// Number of blocks an owner must hold the option before transferring it (0 disables the check)
+    #[serde(default)]
+    pub min_hold_blocks: u64,
+    /// Require exactly one collateral coin and one counter_offer coin, of different denoms
+    #[serde(default)]
+    pub single_denom_mode: bool,
+    /// Signature of the creator over the hash of an off-chain terms document
+    pub signed_metadata: Option<SignedMetadata>,
+    /// Share of the collateral, in basis points (at most 5000), paid to the owner when the option
+    /// expires and is burned. Amounts are rounded down in favour of the creator.
+    pub expiry_split_bps: Option<u64>,
+    /// When the creator exercises an option they still own, skip paying the counter_offer to
+    /// themselves: they send no funds and only receive the collateral back
+    #[serde(default)]
+    pub skip_self_sends: bool,
+    /// Addresses allowed to send the counter_offer and exercise the option on behalf of the owner,
+    /// e.g. a treasury. When set, the owner is only allowed to exercise if listed as well.
+    /// The collateral is always paid to the owner.
+    pub funding_allowlist: Option<Vec<String>>,
+    /// Creator (and first owner) of the option if it is not the sender, e.g. when a router
+    /// contract instantiates options for its users. The sender still provides the collateral.
+    pub on_behalf_of: Option<String>,
+    /// Number of blocks after expiry during which only the owner or creator may burn. Burning by
+    /// anyone else in that window does nothing but emit a `burn_warning` event.
+    #[serde(default)]
+    pub warn_before_burn_blocks: u64,
+    /// Execute accepts a payment of up to this many basis points (at most 10) less than each
+    /// counter_offer coin, to absorb rounding differences. The creator receives what was paid.
+    #[serde(default)]
+    pub payment_tolerance_bps: u64,
+    /// First owner of the option if not the creator, e.g. a broker's client
+    pub owner: Option<String>,
+    /// Contract sent `ReminderExecuteMsg::ExpiryReminder` once by TriggerExpiryReminder
+    pub reminder_target: Option<String>,
+    /// Number of blocks before expiry from which the reminder can be triggered (0 never)
+    #[serde(default)]
+    pub reminder_window_blocks: u64,
+    /// Part of the funds sent that is not collateral: paid to whoever exercises the option, to
+    /// cover their gas, or returned to the creator on burn
+    pub exercise_tip: Option<Coin>,
+    /// If set, the funds sent (collateral and any exercise tip) must be exactly these coins, in
+    /// any order, to catch wallets attaching something other than what the frontend intended
+    pub expected_collateral: Option<Vec<Coin>>,
+    /// Writers sharing the counter_offer on exercise and the collateral on burn, in basis points
+    /// summing up to 10000. The creator alone still manages the option.
+    pub creator_shares: Option<Vec<CreatorShare>>,
+    /// Renounce closes the option and returns the collateral (and any exercise tip) to the
+    /// creator right away, instead of handing the option back to them
+    #[serde(default)]
+    pub renounce_returns_to_creator: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMetadata {
+    /// sha256 hash of the document
+    pub content_hash: [u8; 32],
+    /// 64 byte secp256k1 signature (r, s) over 'content_hash'
+    pub signature: Binary,
+    /// compressed or uncompressed secp256k1 public key the creator's address is derived from
+    pub public_key: Binary,
+    /// must be the creator
+    pub signer: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreatorShare {
+    pub address: String,
+    pub bps: u16,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -17,17 +90,260 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
  Transfer { recipient: String},
   // Owner can transfer the option to a new owner. 'recipient' is a String that is the new owner's wallet address 
- Execute {},
+ Execute {
+     /// Rejects the exercise if it is included after this block, e.g. when broadcast late
+     deadline_height: Option<u64>,
+ },
  // Owner executes unexpired option to execute and get the collateral
  Burn {},
   //Burn will release the collateral if the option is expired
+ /// Owner exercises and has the collateral sent straight to 'callback_contract' along with 'callback_msg'
+ ExerciseWithCallback {
+     callback_contract: String,
+     callback_msg: Binary,
+ },
+ /// Creator re-writes an expired option with new terms, keeping the locked collateral.
+ /// An owner other than the creator is paid their 'expiry_split_bps' share first, as on burn.
+ /// The new counter_offer must not be empty, contain zero amounts or repeat a denom.
+ Roll {
+     new_counter_offer: Vec<Coin>,
+     new_expires: u64,
+ },
+ /// Contract admin records the checksum of the deployed code
+ UpdateCodeChecksum { checksum: Binary },
+ /// Anyone can reclaim funds of 'denom' held by the contract beyond the locked collateral
+ ReclaimExcessFunds { denom: String },
+ /// Creator gives part of the collateral to the current owner, leaving the option otherwise intact.
+ /// Paid through the settlement router, if there is one.
+ Gift { amount: Vec<Coin> },
+ /// Creator or owner attaches a memo only returned to the two of them by the PrivateMemo query
+ SetPrivateMemo { memo: String },
+ /// Owner gives the option back to the creator without any funds moving, or with
+ /// 'renounce_returns_to_creator' closes it and returns the collateral to the creator
+ Renounce {},
+ /// Does nothing and is always rejected with `UnsupportedAction`, so clients probing the
+ /// contract get a clear error. Funds sent along are returned by the failed transaction.
+ Noop {},
+ /// Anyone (e.g. a keeper) notifies the 'reminder_target' that the option is about to expire.
+ /// Only possible once per expiry, within the reminder window.
+ TriggerExpiryReminder {},
+ /// Contract admin blocks addresses from receiving the option or any payout, e.g. for
+ /// sanctions screening. Settlements involving a blocked address fail until it is removed.
+ UpdateBlocklist { add: Vec<String>, remove: Vec<String> },
+ /// Creator replaces the terms of an option nobody else ever owned. The new counter_offer must
+ /// not be empty, contain zero amounts or repeat a denom.
+ Amend { counter_offer: Vec<Coin>, expires: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
    Config{}, 
+   /// Value of exercising right now, given the price of one unit of collateral in the counter_offer denom.
+   /// Only defined for single-denom collateral and counter_offer.
+   IntrinsicValue { price: Decimal },
+   /// All addresses involved in the option, for compliance tooling
+   Counterparties {},
+   /// Who deployed the contract, when, and which code
+   ContractMeta {},
+   /// The original, unmodified creation inputs of the option
+   CreationRecord {},
+   /// The creator's signed off-chain document, if any
+   SignedMetadata {},
+   /// Compact status of the option as a single number, see `StatusCodeResponse`
+   StatusCode {},
+   /// Collateral the contract should hold compared to its actual balance
+   Reconcile {},
+   /// The memo, if 'as_address' is the creator or owner
+   PrivateMemo { as_address: String },
+   /// Denoms of the collateral and counter_offer, e.g. to load token metadata
+   Denoms {},
+   /// How 'funds' compares to the counter_offer, to check a payment before exercising
+   PaymentAssessment { funds: Vec<Coin> },
+   /// Minimum number of blocks between writing (or rolling) an option and its expiry
+   MinExpiryDistance {},
+   /// The messages Execute would send right now for 'sender' paying 'funds', or why it would fail
+   PreviewExerciseMessages { sender: String, funds: Vec<Coin> },
+   /// How often the option changed hands and how long owners held it on average
+   OwnershipStats {},
+   /// Raw storage keys of the option, for tools reading the contract state directly
+   StorageLayout {},
+   /// Hash of the option terms, for off-chain systems to reference or sign, see `DigestResponse`
+   Digest {},
+   /// The latest 'limit' (default all) counter_offer changes by Roll or Amend, oldest first
+   CounterOfferHistory { limit: Option<u32> },
+   /// Actions taken by the contract admin with an id after 'start_after', oldest first
+   AdminLog {
+       start_after: Option<u64>,
+       limit: Option<u32>,
+   },
+}
+
+/// Message sent to the settlement router (if configured) when the option is executed or burned.
+/// The router receives the sum of all legs as funds and is expected to pay out each leg.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RouterExecuteMsg {
+    RouteSettlement { legs: Vec<Leg> },
+}
+
+/// Message sent to the reminder target (if configured) when TriggerExpiryReminder is called.
+/// The sender is the option contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderExecuteMsg {
+    ExpiryReminder { expires: u64 },
+}
+
+/// A single payout of a settlement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Leg {
+    pub to: String,
+    pub amount: Vec<Coin>,
 }
 
+/// Set as `Response.data` by Execute, Burn and a Renounce closing the option: the option as it
+/// was stored right before it was settled and deleted. Contracts triggering settlement via submessages can decode it in their reply.
+pub type SettlementData = State;
+
+pub type ContractMetaResponse = ContractMeta;
+
+pub type CreationRecordResponse = CreationRecord;
+
 // We define a custom struct for each query response. In this case, the query response is the State struct, imported from state.rs  
 pub type ConfigResponse = State;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IntrinsicValueResponse {
+    // max(0, price * collateral - counter_offer), in units of the counter_offer denom
+    pub value: Uint128,
+}
+
+/// 0 active, 1 executed, 2 burned, 3 cancelled (renounced with 'renounce_returns_to_creator'),
+/// 4 expired but not burned yet
+pub type StatusCodeResponse = u8;
+
+pub const STATUS_ACTIVE: u8 = 0;
+pub const STATUS_EXECUTED: u8 = 1;
+pub const STATUS_BURNED: u8 = 2;
+pub const STATUS_CANCELLED: u8 = 3;
+pub const STATUS_EXPIRED: u8 = 4;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReconcileResponse {
+    // collateral and exercise tip locked by the option, empty once it is settled
+    pub recorded: Vec<Coin>,
+    // actual bank balance of the contract
+    pub held: Vec<Coin>,
+    // per denom absolute difference between the two, compare 'recorded' and 'held' for
+    // whether it is untracked funds or a shortfall
+    pub discrepancies: Vec<Coin>,
+}
+
+/// Only an access check: the memo is not encrypted and anyone can read it from the chain state.
+/// It just keeps it out of the regular queries so indexers do not pick it up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrivateMemoResponse {
+    // None if there is no memo or 'as_address' is not a party of the option
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomsResponse {
+    // sorted and without duplicates
+    pub collateral_denoms: Vec<String>,
+    pub counter_offer_denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PaymentAssessmentResponse {
+    pub status: PaymentStatus,
+    // whether Execute would accept the payment, which includes shortfalls within the
+    // payment tolerance. Does not check the sender or the expiry.
+    pub accepted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Exact {},
+    /// Coins sent beyond the counter_offer
+    Over {
+        surplus: Vec<Coin>,
+    },
+    /// Coins of the counter_offer missing, reported even if something else is overpaid
+    Under {
+        shortfall: Vec<Coin>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PreviewExerciseMessagesResponse {
+    // in the order Execute sends them, empty if it would fail
+    pub messages: Vec<CosmosMsg>,
+    // the error Execute would fail with, and its `ContractError` code
+    pub error: Option<String>,
+    pub error_code: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipStatsResponse {
+    pub total_transfers: u64,
+    // over completed holdings only, rounded down. 0 before the first transfer
+    pub avg_hold_blocks: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterOfferHistoryResponse {
+    pub changes: Vec<CounterOfferChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminLogEntry {
+    pub id: u64,
+    pub action: AdminAction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminLogResponse {
+    pub entries: Vec<AdminLogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DigestResponse {
+    // sha256 of the JSON object {"creator","collateral","counter_offer","expires"}, in this order
+    // and without whitespace. Coins are merged and sorted by denom, zero amounts left out.
+    pub digest: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StorageLayoutResponse {
+    // key of the State item, stored as JSON
+    pub config_key: String,
+    // namespace of a map of options. None, a contract holds a single option under 'config_key'
+    pub options_namespace: Option<String>,
+    // key of a separate option counter. None, 'seq' is part of the State
+    pub counter_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinExpiryDistanceResponse {
+    pub min_expiry_distance: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterpartiesResponse {
+    // creator of the option, receives the counter_offer
+    pub writer: Addr,
+    // current owner, may exercise
+    pub holder: Addr,
+    // receiver of protocol fees, if any are configured
+    pub fee_collector: Option<Addr>,
+    // settlement router that is paid instead of the parties, if configured
+    pub payee: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMetadataResponse {
+    pub signed_metadata: Option<SignedMetadataRecord>,
+}
@@ -1,8 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Binary, Coin};
+use cw_storage_plus::{Item, Map};
+
+use crate::msg::InstantiateMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
@@ -14,12 +16,151 @@ pub struct State {
     pub collateral: Vec<Coin>, 
     pub counter_offer: Vec<Coin>, 
     pub expires: u64, 
+    // If set, settlement funds are routed through this contract instead of sent directly
+    pub settlement_router: Option<Addr>,
+    // The owner may only transfer once 'min_hold_blocks' have passed since 'acquired_at_height'
+    pub min_hold_blocks: u64,
+    pub acquired_at_height: u64,
+    // Incremented by every state-changing action, starting at 1 on instantiation
+    pub seq: u64,
+    // If set, collateral and counter_offer are a single coin each, of different denoms
+    pub single_denom_mode: bool,
+    // Off-chain terms document the creator signed, if any
+    pub signed_metadata: Option<SignedMetadataRecord>,
+    // Share of the collateral in basis points paid to the owner on burn, the rest goes to the creator
+    pub expiry_split_bps: Option<u64>,
+    // If set, the creator exercising their own option pays no counter_offer to themselves
+    pub skip_self_sends: bool,
+    // For this many blocks after expiry only the owner and creator may burn
+    pub warn_before_burn_blocks: u64,
+    // Execute accepts payments up to this many basis points below the counter_offer, per coin
+    pub payment_tolerance_bps: u64,
+    // If set, only these addresses may exercise (and fund) the option, the owner still gets the collateral
+    pub funding_allowlist: Option<Vec<Addr>>,
+    // Notified once within 'reminder_window_blocks' before expiry, 'reminded' is reset by Roll
+    pub reminder_target: Option<Addr>,
+    pub reminder_window_blocks: u64,
+    pub reminded: bool,
+    // Held on top of the collateral, paid to the exerciser on exercise and to the creator on burn
+    pub exercise_tip: Option<Coin>,
+    // If set, creator payouts are split between these addresses instead of going to 'creator'
+    pub creator_shares: Option<Vec<CreatorShareRecord>>,
+    // If set, Renounce closes the option and returns the collateral to the creator
+    pub renounce_returns_to_creator: bool,
+}
+
+// A verified secp256k1 signature by 'signer' over the sha256 hash of an off-chain document.
+// 'signer' is the address of the public key as derived on Cosmos SDK chains, so the record proves
+// the signer holds the key of that account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedMetadataRecord {
+    pub content_hash: [u8; 32],
+    pub signature: Binary,
+    pub public_key: Binary,
+    pub signer: Addr,
+}
+
+// Share in basis points of the creator payouts, all shares of an option sum up to 10000
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreatorShareRecord {
+    pub address: Addr,
+    pub bps: u16,
 }
 
 pub const CONFIG_KEY: &str = "config";
 // Item stores one typed item at the given key. So CONFIG is storing the State struct to the given key "CONFIG_KEY"
 pub const CONFIG: Item<State> = Item::new(CONFIG_KEY);
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractMeta {
+    // the address that instantiated the contract, and the block it happened in
+    pub deployer: Addr,
+    pub deploy_block: u64,
+    // block time in seconds
+    pub deploy_time: u64,
+    // checksum of the wasm code, if known. Not available from the environment before CosmWasm 1.2,
+    // so the contract admin can set it with UpdateCodeChecksum
+    pub code_checksum: Option<Binary>,
+}
+
+pub const CONTRACT_META: Item<ContractMeta> = Item::new("contract_meta");
+
+// Addresses the contract admin blocked from receiving the option or any funds from it
+pub const BLOCKLIST: Item<Vec<Addr>> = Item::new("blocklist");
+
+// The inputs the option was created from, exactly as received. Written once on instantiation
+// and never changed or removed, so auditors can check the current state against it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreationRecord {
+    pub msg: InstantiateMsg,
+    pub sender: Addr,
+    pub funds: Vec<Coin>,
+    pub height: u64,
+    // block time in seconds
+    pub time: u64,
+}
+
+pub const CREATION_RECORD: Item<CreationRecord> = Item::new("creation_record");
+
+// Completed ownership periods, ended by Transfer or Renounce. Kept after settlement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct OwnershipStats {
+    pub total_transfers: u64,
+    // blocks each previous owner held the option, summed up
+    pub total_hold_blocks: u64,
+}
+
+pub const OWNERSHIP_STATS: Item<OwnershipStats> = Item::new("ownership_stats");
+
+// A new counter_offer set by Roll or Amend, the initial one is in the CreationRecord
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterOfferChange {
+    pub counter_offer: Vec<Coin>,
+    pub height: u64,
+}
+
+// Oldest first, only the last MAX_COUNTER_OFFER_HISTORY changes are kept. Kept after settlement.
+pub const COUNTER_OFFER_HISTORY: Item<Vec<CounterOfferChange>> = Item::new("counter_offer_history");
+
+// How and when the option ended. Written when the option is settled and deleted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Settlement {
+    pub kind: SettlementKind,
+    pub height: u64,
+    // the final seq of the option, bumped by the settling action
+    pub seq: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementKind {
+    Executed,
+    Burned,
+    // declined by the owner, with 'renounce_returns_to_creator'
+    Renounced,
+}
+
+pub const SETTLEMENT: Item<Settlement> = Item::new("settlement");
+
+// Kept apart from State so it is never part of the config or settlement data
+pub const PRIVATE_MEMO_KEY: &str = "private_memo";
+pub const PRIVATE_MEMO: Item<String> = Item::new(PRIVATE_MEMO_KEY);
+
+// An action taken by the contract admin, written by the handler that executed it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminAction {
+    // same as the "action" attribute of the response
+    pub kind: String,
+    // the ExecuteMsg as executed, serialized to JSON
+    pub payload_json: String,
+    pub admin: Addr,
+    pub height: u64,
+}
+
+// Keyed by an id counting up from 1 in the order the actions were executed. Kept after settlement.
+pub const ADMIN_LOG: Map<u64, AdminAction> = Map::new("admin_log");
+pub const ADMIN_LOG_COUNT: Item<u64> = Item::new("admin_log_count");
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -39,6 +180,23 @@ mod test {
             collateral: coins(40, "ETH"), 
             counter_offer: coins(40, "ETH"), 
             expires: 1234, 
+            settlement_router: None,
+            min_hold_blocks: 0,
+            acquired_at_height: 1000,
+            seq: 1,
+            single_denom_mode: false,
+            signed_metadata: None,
+            expiry_split_bps: None,
+            skip_self_sends: false,
+            warn_before_burn_blocks: 0,
+            payment_tolerance_bps: 0,
+            funding_allowlist: None,
+            reminder_target: None,
+            reminder_window_blocks: 0,
+            reminded: false,
+            exercise_tip: None,
+            creator_shares: None,
+            renounce_returns_to_creator: false,
         };
         CONFIG.save(&mut store, &cfg).unwrap();
         assert_eq!(cfg, CONFIG.load(&store).unwrap());
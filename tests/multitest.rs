@@ -0,0 +1,331 @@
+//! Settlement tests on a simulated chain (cw-multi-test), checking the balances that actually
+//! change instead of the messages the contract returns. New settlement features should add their
+//! cases to `Case` and the expectations below, so every path is checked the same way.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use cosmwasm_std::{coin, to_binary, Addr, Coin, CosmosMsg, Empty, WasmMsg};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use serde::Serialize;
+
+use simple_option::contract::{execute, instantiate, query, reply};
+use simple_option::msg::{ExecuteMsg, InstantiateMsg};
+use simple_option::ContractError;
+
+const CREATOR: &str = "creator";
+const OWNER: &str = "owner";
+
+fn option_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+}
+
+// an option written by CREATOR and transferred to OWNER
+struct Case {
+    collateral: Vec<Coin>,
+    counter_offer: Vec<Coin>,
+    exercise_tip: Option<Coin>,
+}
+
+impl Case {
+    // everything the creator sends when writing the option
+    fn deposit(&self) -> Vec<Coin> {
+        let deposit: Vec<Coin> = self
+            .collateral
+            .iter()
+            .chain(&self.exercise_tip)
+            .cloned()
+            .collect();
+        total(&deposit, &[])
+            .into_iter()
+            .map(|(denom, amount)| coin(amount as u128, denom))
+            .collect()
+    }
+}
+
+struct Harness {
+    app: App,
+    option: Addr,
+}
+
+impl Harness {
+    fn new(case: &Case) -> Self {
+        let mut app = App::new(|router, _, storage| {
+            // enough to write and exercise the option several times
+            let funds = |coins: &[Coin]| -> Vec<Coin> {
+                coins
+                    .iter()
+                    .map(|c| coin(c.amount.u128() * 10, &c.denom))
+                    .collect()
+            };
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(CREATOR), funds(&case.deposit()))
+                .unwrap();
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(OWNER), funds(&case.counter_offer))
+                .unwrap();
+        });
+        let code_id = app.store_code(option_contract());
+        let msg = InstantiateMsg {
+            counter_offer: case.counter_offer.clone(),
+            expires: app.block_info().height + 10_000,
+            exercise_tip: case.exercise_tip.clone(),
+            ..Default::default()
+        };
+        let option = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(CREATOR),
+                &msg,
+                &case.deposit(),
+                "option",
+                None,
+            )
+            .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            recipient: OWNER.to_string(),
+        };
+        app.execute_contract(Addr::unchecked(CREATOR), option.clone(), &msg, &[])
+            .unwrap();
+        Harness { app, option }
+    }
+
+    fn balance(&self, address: &str) -> BTreeMap<String, i128> {
+        self.app
+            .wrap()
+            .query_all_balances(address)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.denom, c.amount.u128() as i128))
+            .collect()
+    }
+
+    // runs 'action' and returns how the balances of CREATOR and OWNER changed, per denom
+    fn deltas(
+        &mut self,
+        action: impl FnOnce(&mut App, &Addr),
+    ) -> (BTreeMap<String, i128>, BTreeMap<String, i128>) {
+        let before = (self.balance(CREATOR), self.balance(OWNER));
+        action(&mut self.app, &self.option);
+        let after = (self.balance(CREATOR), self.balance(OWNER));
+        (delta(&before.0, &after.0), delta(&before.1, &after.1))
+    }
+}
+
+fn delta(
+    before: &BTreeMap<String, i128>,
+    after: &BTreeMap<String, i128>,
+) -> BTreeMap<String, i128> {
+    let mut delta = after.clone();
+    for (denom, amount) in before {
+        *delta.entry(denom.clone()).or_default() -= amount;
+    }
+    delta.retain(|_, amount| *amount != 0);
+    delta
+}
+
+// the signed sum of coins per denom, like a delta
+fn total(plus: &[Coin], minus: &[Coin]) -> BTreeMap<String, i128> {
+    let mut total = BTreeMap::new();
+    for c in plus {
+        *total.entry(c.denom.clone()).or_default() += c.amount.u128() as i128;
+    }
+    for c in minus {
+        *total.entry(c.denom.clone()).or_default() -= c.amount.u128() as i128;
+    }
+    total.retain(|_, amount| *amount != 0);
+    total
+}
+
+fn cases() -> Vec<Case> {
+    vec![
+        // single coin each
+        Case {
+            collateral: vec![coin(1, "BTC")],
+            counter_offer: vec![coin(40, "ETH")],
+            exercise_tip: None,
+        },
+        // multiple coins each
+        Case {
+            collateral: vec![coin(1, "BTC"), coin(7, "OSMO")],
+            counter_offer: vec![coin(5, "ATOM"), coin(40, "ETH")],
+            exercise_tip: None,
+        },
+        // denoms on both sides
+        Case {
+            collateral: vec![coin(1, "BTC"), coin(30, "ETH")],
+            counter_offer: vec![coin(40, "ETH")],
+            exercise_tip: None,
+        },
+        // a tip on top of the collateral, in one of its denoms
+        Case {
+            collateral: vec![coin(1, "BTC"), coin(30, "ETH")],
+            counter_offer: vec![coin(40, "ETH")],
+            exercise_tip: Some(coin(2, "BTC")),
+        },
+    ]
+}
+
+#[test]
+fn execute_moves_both_legs() {
+    for case in cases() {
+        let mut harness = Harness::new(&case);
+        let (creator, owner) = harness.deltas(|app, option| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                option.clone(),
+                &ExecuteMsg::Execute {
+                    deadline_height: None,
+                },
+                &case.counter_offer,
+            )
+            .unwrap();
+        });
+
+        // the creator is paid the counter_offer, the owner swaps it for the collateral and tip
+        assert_eq!(creator, total(&case.counter_offer, &[]));
+        assert_eq!(owner, total(&case.deposit(), &case.counter_offer));
+        assert_eq!(harness.balance(harness.option.as_str()), BTreeMap::new());
+    }
+}
+
+#[test]
+fn burn_returns_collateral() {
+    for case in cases() {
+        let mut harness = Harness::new(&case);
+        harness.app.update_block(|block| block.height += 10_000);
+        let (creator, owner) = harness.deltas(|app, option| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                option.clone(),
+                &ExecuteMsg::Burn {},
+                &[],
+            )
+            .unwrap();
+        });
+
+        assert_eq!(creator, total(&case.deposit(), &[]));
+        assert_eq!(owner, BTreeMap::new());
+        assert_eq!(harness.balance(harness.option.as_str()), BTreeMap::new());
+    }
+}
+
+// a message from an old frontend, unknown to the contract
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum LegacyExecuteMsg {
+    ExerciseAll {},
+}
+
+#[test]
+fn rejected_execute_keeps_funds() {
+    fn assert_keeps_funds(msg: &(impl Serialize + Debug)) {
+        let case = &cases()[0];
+        let mut harness = Harness::new(case);
+        let (creator, owner) = harness.deltas(|app, option| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                option.clone(),
+                msg,
+                &case.counter_offer,
+            )
+            .unwrap_err();
+        });
+
+        assert_eq!(creator, BTreeMap::new());
+        assert_eq!(owner, BTreeMap::new());
+        assert_eq!(
+            harness.balance(harness.option.as_str()),
+            total(&case.deposit(), &[])
+        );
+    }
+
+    assert_keeps_funds(&ExecuteMsg::Noop {});
+    // only rejected when the message is parsed, before the contract runs
+    assert_keeps_funds(&LegacyExecuteMsg::ExerciseAll {});
+}
+
+#[test]
+fn settling_twice_in_one_transaction_reverts() {
+    let case = &cases()[0];
+    let settle_twice = |option: &Addr, msg: ExecuteMsg, funds: &[Coin]| -> Vec<CosmosMsg> {
+        let msg = WasmMsg::Execute {
+            contract_addr: option.to_string(),
+            msg: to_binary(&msg).unwrap(),
+            funds: funds.to_vec(),
+        };
+        vec![msg.clone().into(), msg.into()]
+    };
+
+    // exercised twice
+    let mut harness = Harness::new(case);
+    let msgs = settle_twice(
+        &harness.option,
+        ExecuteMsg::Execute {
+            deadline_height: None,
+        },
+        &case.counter_offer,
+    );
+    let (creator, owner) = harness.deltas(|app, _| {
+        let err = app.execute_multi(Addr::unchecked(OWNER), msgs).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::AlreadySettled { .. })
+        ));
+    });
+    assert_eq!(creator, BTreeMap::new());
+    assert_eq!(owner, BTreeMap::new());
+
+    // burned twice
+    harness.app.update_block(|block| block.height += 10_000);
+    let msgs = settle_twice(&harness.option, ExecuteMsg::Burn {}, &[]);
+    let (creator, owner) = harness.deltas(|app, _| {
+        let err = app.execute_multi(Addr::unchecked(OWNER), msgs).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::AlreadySettled { .. })
+        ));
+    });
+    assert_eq!(creator, BTreeMap::new());
+    assert_eq!(owner, BTreeMap::new());
+    assert_eq!(
+        harness.balance(harness.option.as_str()),
+        total(&case.deposit(), &[])
+    );
+}
+
+#[test]
+fn execute_and_burn_never_overlap() {
+    let case = &cases()[0];
+    let mut harness = Harness::new(case);
+    // the first block in which the option can be burned
+    harness.app.update_block(|block| block.height += 10_000);
+
+    let (creator, owner) = harness.deltas(|app, option| {
+        let err = app
+            .execute_contract(
+                Addr::unchecked(OWNER),
+                option.clone(),
+                &ExecuteMsg::Execute {
+                    deadline_height: None,
+                },
+                &case.counter_offer,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::OptionExpired { .. })
+        ));
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            option.clone(),
+            &ExecuteMsg::Burn {},
+            &[],
+        )
+        .unwrap();
+    });
+    assert_eq!(creator, total(&case.deposit(), &[]));
+    assert_eq!(owner, BTreeMap::new());
+}
@@ -0,0 +1,181 @@
+//! Runs the JSON scenarios in tests/scenarios on a simulated chain (cw-multi-test), so lifecycle
+//! cases can be added without writing Rust. `cargo test scenario_` runs them all.
+//!
+//! A scenario sets the initial bank balances and lists steps, each one of:
+//! - `instantiate`: writes the option, at most once per scenario
+//! - `execute`: sends an ExecuteMsg to the option
+//! - `advance`: moves the chain forward by a number of blocks (it starts at height 12345)
+//! - `balance`: checks all balances of an address, `"option"` being the option contract
+//! - `query`: checks the JSON result of a QueryMsg
+//!
+//! `instantiate` and `execute` take an optional `error`, the expected `ContractError` code.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use cosmwasm_std::{to_binary, to_vec, Addr, Coin, Empty, QueryRequest, WasmQuery};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use serde::Deserialize;
+
+use simple_option::contract::{execute, instantiate, query, reply};
+use simple_option::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use simple_option::ContractError;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    #[serde(default)]
+    balances: BTreeMap<String, Vec<Coin>>,
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum Step {
+    Instantiate {
+        sender: String,
+        msg: Box<InstantiateMsg>,
+        #[serde(default)]
+        funds: Vec<Coin>,
+        error: Option<u32>,
+    },
+    Execute {
+        sender: String,
+        msg: ExecuteMsg,
+        #[serde(default)]
+        funds: Vec<Coin>,
+        error: Option<u32>,
+    },
+    Advance {
+        blocks: u64,
+    },
+    Balance {
+        address: String,
+        expect: Vec<Coin>,
+    },
+    Query {
+        msg: QueryMsg,
+        expect: serde_json::Value,
+    },
+}
+
+fn option_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(execute, instantiate, query).with_reply(reply))
+}
+
+fn parse(name: &str, json: &str) -> Result<Scenario, String> {
+    serde_json::from_str(json).map_err(|err| format!("malformed scenario {}: {}", name, err))
+}
+
+// checks the outcome of a message against the expected error code, if any
+fn check<T>(at: &str, res: anyhow::Result<T>, error: Option<u32>) -> Option<T> {
+    match (res, error) {
+        (Ok(value), None) => Some(value),
+        (Ok(_), Some(code)) => panic!("{}: succeeded, expected error code {}", at, code),
+        (Err(err), expected) => {
+            let code = err
+                .downcast_ref::<ContractError>()
+                .map(ContractError::error_code);
+            if code.is_none() || code != expected {
+                panic!("{}: failed with {}, expected {:?}", at, err, expected);
+            }
+            None
+        }
+    }
+}
+
+fn run(name: &str, scenario: Scenario) {
+    let mut app = App::new(|router, _, storage| {
+        for (address, coins) in &scenario.balances {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(address), coins.clone())
+                .unwrap();
+        }
+    });
+    let code_id = app.store_code(option_contract());
+    let mut option: Option<Addr> = None;
+
+    for (i, step) in scenario.steps.into_iter().enumerate() {
+        let at = format!("{} step {}", name, i);
+        let option_addr = || {
+            option
+                .clone()
+                .unwrap_or_else(|| panic!("{}: option not instantiated", at))
+        };
+        match step {
+            Step::Instantiate {
+                sender,
+                msg,
+                funds,
+                error,
+            } => {
+                assert!(option.is_none(), "{}: option already instantiated", at);
+                let sender = Addr::unchecked(sender);
+                let res = app.instantiate_contract(code_id, sender, &msg, &funds, "option", None);
+                option = check(&at, res, error);
+            }
+            Step::Execute {
+                sender,
+                msg,
+                funds,
+                error,
+            } => {
+                let sender = Addr::unchecked(sender);
+                let res = app.execute_contract(sender, option_addr(), &msg, &funds);
+                check(&at, res, error);
+            }
+            Step::Advance { blocks } => app.update_block(|block| block.height += blocks),
+            Step::Balance { address, expect } => {
+                let address = match address.as_str() {
+                    "option" => option_addr().to_string(),
+                    _ => address,
+                };
+                let balance = app.wrap().query_all_balances(&address).unwrap();
+                assert_eq!(balance, expect, "{}: balance of {}", at, address);
+            }
+            Step::Query { msg, expect } => {
+                let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: option_addr().to_string(),
+                    msg: to_binary(&msg).unwrap(),
+                });
+                let res = app
+                    .wrap()
+                    .raw_query(&to_vec(&request).unwrap())
+                    .unwrap()
+                    .unwrap();
+                let value: serde_json::Value = serde_json::from_slice(&res).unwrap();
+                assert_eq!(value, expect, "{}: query result", at);
+            }
+        }
+    }
+}
+
+#[test]
+fn scenario_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| matches!(path.extension(), Some(ext) if ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios found");
+
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let scenario = parse(&name, &fs::read_to_string(&path).unwrap()).unwrap_or_else(|err| {
+            panic!("{}", err);
+        });
+        run(&name, scenario);
+    }
+}
+
+#[test]
+fn scenario_malformed_is_reported() {
+    let json = r#"{ "steps": [{ "advnce": { "blocks": 1 } }] }"#;
+    let err = parse("typo.json", json).err().unwrap();
+    assert!(err.starts_with("malformed scenario typo.json: unknown variant `advnce`"));
+    assert!(err.contains("line 1"));
+}
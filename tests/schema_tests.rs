@@ -0,0 +1,194 @@
+//! Checks the JSON schema files generated by `cargo schema` (see src/bin/schema.rs).
+//! If one of these fails after changing a message, regenerate the schema and commit it.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+const DRAFT_07: &str = "http://json-schema.org/draft-07/schema#";
+
+fn load(file: &str) -> Value {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "schema", file]
+        .iter()
+        .collect();
+    let data = fs::read(&path).unwrap_or_else(|e| panic!("cannot read {:?}: {}", path, e));
+    serde_json::from_slice(&data).unwrap_or_else(|e| panic!("invalid json in {:?}: {}", path, e))
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    let mut fields: Vec<&str> = schema["required"]
+        .as_array()
+        .expect("missing required")
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    fields.sort_unstable();
+    fields
+}
+
+// every variant of an externally tagged enum is an object requiring exactly its own tag
+fn variants(schema: &Value) -> Vec<&str> {
+    let mut names: Vec<&str> = schema["oneOf"]
+        .as_array()
+        .expect("missing oneOf")
+        .iter()
+        .map(|variant| {
+            let tags = required(variant);
+            assert_eq!(tags.len(), 1, "variant must require its tag: {}", variant);
+            assert!(variant["properties"].get(tags[0]).is_some());
+            tags[0]
+        })
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn all_schemas_have_draft_and_title() {
+    for (file, title) in [
+        ("instantiate_msg.json", "InstantiateMsg"),
+        ("execute_msg.json", "ExecuteMsg"),
+        ("query_msg.json", "QueryMsg"),
+        ("state.json", "State"),
+        ("router_execute_msg.json", "RouterExecuteMsg"),
+        ("reminder_execute_msg.json", "ReminderExecuteMsg"),
+        ("settlement_data.json", "SettlementData"),
+        ("admin_log_response.json", "AdminLogResponse"),
+        ("config_response.json", "ConfigResponse"),
+        ("contract_meta_response.json", "ContractMetaResponse"),
+        (
+            "counter_offer_history_response.json",
+            "CounterOfferHistoryResponse",
+        ),
+        ("counterparties_response.json", "CounterpartiesResponse"),
+        ("denoms_response.json", "DenomsResponse"),
+        ("digest_response.json", "DigestResponse"),
+        ("creation_record_response.json", "CreationRecordResponse"),
+        ("intrinsic_value_response.json", "IntrinsicValueResponse"),
+        (
+            "min_expiry_distance_response.json",
+            "MinExpiryDistanceResponse",
+        ),
+        (
+            "payment_assessment_response.json",
+            "PaymentAssessmentResponse",
+        ),
+        ("ownership_stats_response.json", "OwnershipStatsResponse"),
+        (
+            "preview_exercise_messages_response.json",
+            "PreviewExerciseMessagesResponse",
+        ),
+        ("private_memo_response.json", "PrivateMemoResponse"),
+        ("reconcile_response.json", "ReconcileResponse"),
+        ("signed_metadata_response.json", "SignedMetadataResponse"),
+        ("status_code_response.json", "StatusCodeResponse"),
+        ("storage_layout_response.json", "StorageLayoutResponse"),
+    ] {
+        let schema = load(file);
+        assert_eq!(schema["$schema"], DRAFT_07, "{}", file);
+        assert_eq!(schema["title"], title, "{}", file);
+    }
+}
+
+#[test]
+fn instantiate_msg_schema() {
+    let schema = load("instantiate_msg.json");
+    assert_eq!(required(&schema), vec!["counter_offer", "expires"]);
+}
+
+#[test]
+fn execute_msg_schema() {
+    let schema = load("execute_msg.json");
+    assert_eq!(
+        variants(&schema),
+        vec![
+            "amend",
+            "burn",
+            "execute",
+            "exercise_with_callback",
+            "gift",
+            "noop",
+            "reclaim_excess_funds",
+            "renounce",
+            "roll",
+            "set_private_memo",
+            "transfer",
+            "trigger_expiry_reminder",
+            "update_blocklist",
+            "update_code_checksum",
+        ]
+    );
+    let transfer = schema["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["properties"].get("transfer").is_some())
+        .unwrap();
+    assert_eq!(
+        required(&transfer["properties"]["transfer"]),
+        vec!["recipient"]
+    );
+}
+
+#[test]
+fn query_msg_schema() {
+    let schema = load("query_msg.json");
+    assert_eq!(
+        variants(&schema),
+        vec![
+            "admin_log",
+            "config",
+            "contract_meta",
+            "counter_offer_history",
+            "counterparties",
+            "creation_record",
+            "denoms",
+            "digest",
+            "intrinsic_value",
+            "min_expiry_distance",
+            "ownership_stats",
+            "payment_assessment",
+            "preview_exercise_messages",
+            "private_memo",
+            "reconcile",
+            "signed_metadata",
+            "status_code",
+            "storage_layout",
+        ]
+    );
+}
+
+#[test]
+fn state_and_config_response_schema() {
+    let fields = vec![
+        "acquired_at_height",
+        "collateral",
+        "counter_offer",
+        "creator",
+        "expires",
+        "min_hold_blocks",
+        "owner",
+        "payment_tolerance_bps",
+        "reminded",
+        "reminder_window_blocks",
+        "renounce_returns_to_creator",
+        "seq",
+        "single_denom_mode",
+        "skip_self_sends",
+        "warn_before_burn_blocks",
+    ];
+    assert_eq!(required(&load("state.json")), fields);
+    assert_eq!(required(&load("config_response.json")), fields);
+    assert_eq!(required(&load("settlement_data.json")), fields);
+}
+
+#[test]
+fn router_execute_msg_schema() {
+    let schema = load("router_execute_msg.json");
+    assert_eq!(variants(&schema), vec!["route_settlement"]);
+    assert_eq!(
+        required(&schema["definitions"]["Leg"]),
+        vec!["amount", "to"]
+    );
+}